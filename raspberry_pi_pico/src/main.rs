@@ -27,6 +27,19 @@ use usb_device::LangID;
 #[used]
 pub static BOOT2: [u8; 256] = rp2040_boot2::BOOT_LOADER_GENERIC_03H;
 
+// reasons sent by the main program when the chosen action can't be done,
+// in the same order as their codes (-10, -11, ...)
+const REJECTIONS: [&str; 8] = [
+    "no fish in\nbackpack",
+    "no wood in\nbackpack",
+    "no rocks in\nbackpack",
+    "no coins in\nbackpack",
+    "no known\nmarket",
+    "no known\nbank",
+    "backpack\nis full",
+    "energy\nis full",
+];
+
 // entry point for the program
 #[entry]
 fn main() -> ! {
@@ -245,6 +258,13 @@ fn main() -> ! {
                     display.clear().unwrap();
                     let _ = write!(display, "\nScore:\n{}", msg);
                 }
+                // the msg read is the reason why the chosen action was rejected,
+                // the main program goes on by itself so no input is needed
+                else if msg <= -10f32 {
+                    let reason = REJECTIONS.get((-10f32 - msg) as usize).unwrap_or(&"can't do\nthat");
+                    display.clear().unwrap();
+                    let _ = write!(display, "\n{}", reason);
+                }
                 // the msg read is telling the pico to do something,
                 // for now any negative value signals that the main program
                 // is ready to receive input
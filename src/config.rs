@@ -0,0 +1,231 @@
+use serde::Deserialize;
+
+// tuning parameters of the robot, loaded from a TOML file passed at startup
+// every field is optional in the file, missing ones keep the default value
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct PioneerConfig {
+    // strategy used to decide the objectives in the Praying phase: "utility", "heuristic" or "goap"
+    pub strategy: String,
+
+    // energy level under which the robot stops to charge, and the level it charges up to
+    pub low_energy: usize,
+    pub recharge_to: usize,
+    // level to charge up to when there isn't enough energy to build a bridge
+    pub bridge_recharge_to: usize,
+    // how much to charge when there isn't enough energy to deposit
+    pub deposit_recharge: usize,
+
+    // fractions of the backpack: the robot sells when it's fuller than the first
+    // and gathers when it's emptier than the second
+    pub sell_ratio: f32,
+    pub gather_ratio: f32,
+
+    // chances, each tick, to pick up content and to use the spyglass while moving
+    pub pickup_chance: f32,
+    pub spyglass_chance: f32,
+
+    // radius of the spyglass when looking for a place for the tent, while moving and in manual mode
+    pub tent_spyglass_radius: usize,
+    pub moving_spyglass_radius: usize,
+    pub manual_spyglass_radius: usize,
+    // the spyglass used for exploring covers the size of the map divided by this
+    pub exploring_spyglass_divisor: usize,
+
+    // fraction of the map to explore before the game can end
+    pub end_coverage: f32,
+
+    // delays in milliseconds: each tick when the gui is off, while waiting and before asking the pilot
+    pub tick_delay_ms: u64,
+    pub waiting_delay_ms: u64,
+    pub pilot_delay_ms: u64,
+
+    // budget of a move: a base amount plus some for each tile of distance
+    pub moving_budget_ticks: usize,
+    pub moving_budget_ticks_per_tile: usize,
+    pub moving_budget_energy: usize,
+    pub moving_budget_energy_per_tile: usize,
+    // budget of the other tasks that can get stuck (gathering, selling, depositing, exploring)
+    pub task_budget_ticks: usize,
+    pub task_budget_energy: usize,
+
+    // energy the robot keeps aside when deciding whether it can get somewhere without charging
+    pub energy_reserve: usize,
+
+    // days the robot stays away from unreachable targets,
+    // false positives of the spyglass and content that vanished
+    pub unreachable_expiry_days: usize,
+    pub false_positive_expiry_days: usize,
+    pub vanished_expiry_days: usize,
+    // days after which a spyglass hit the robot never got to isn't checked anymore
+    pub spyglass_hit_expiry_days: usize,
+
+    // days after which a visited place counts as unexplored again,
+    // and after which a depleted market or bank is worth another try
+    pub revisit_days: usize,
+    pub market_restock_days: usize,
+
+    // positions remembered while moving, longest cycle of positions to look for,
+    // and ticks without getting closer to the destination after which the robot is considered stuck
+    pub stuck_history: usize,
+    pub stuck_max_cycle: usize,
+    pub stuck_progress_ticks: usize,
+
+    // how far a target can be for the robot to walk up to it, when it isn't right next to it
+    pub face_target_radius: usize,
+
+    // buildings, markets and banks this close to each other belong to the same town
+    pub town_radius: usize,
+
+    // seed of the random choices of the robot, a random one is used (and printed) if missing
+    pub seed: Option<u64>,
+}
+
+impl Default for PioneerConfig {
+    fn default() -> Self {
+        PioneerConfig {
+            strategy: "utility".to_string(),
+
+            low_energy: 150,
+            recharge_to: 250,
+            bridge_recharge_to: 300,
+            deposit_recharge: 100,
+
+            sell_ratio: 0.8,
+            gather_ratio: 0.6,
+
+            pickup_chance: 0.25,
+            spyglass_chance: 0.1,
+
+            tent_spyglass_radius: 3,
+            moving_spyglass_radius: 5,
+            manual_spyglass_radius: 10,
+            exploring_spyglass_divisor: 2,
+
+            end_coverage: 0.75,
+
+            tick_delay_ms: 500,
+            waiting_delay_ms: 100,
+            pilot_delay_ms: 500,
+
+            moving_budget_ticks: 20,
+            moving_budget_ticks_per_tile: 3,
+            moving_budget_energy: 200,
+            moving_budget_energy_per_tile: 20,
+            task_budget_ticks: 50,
+            task_budget_energy: 500,
+
+            energy_reserve: 100,
+
+            unreachable_expiry_days: 2,
+            false_positive_expiry_days: 5,
+            vanished_expiry_days: 2,
+            spyglass_hit_expiry_days: 2,
+
+            revisit_days: 5,
+            market_restock_days: 3,
+
+            stuck_history: 32,
+            stuck_max_cycle: 6,
+            stuck_progress_ticks: 15,
+
+            face_target_radius: 3,
+
+            town_radius: 4,
+
+            seed: None,
+        }
+    }
+}
+
+impl PioneerConfig {
+    pub fn load(path: &str) -> Result<PioneerConfig, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("can't read {path}: {e}"))?;
+        let config: PioneerConfig = toml::from_str(&text).map_err(|e| format!("can't parse {path}: {e}"))?;
+        config.validate().map_err(|e| format!("invalid {path}: {e}"))?;
+        Ok(config)
+    }
+
+    // values that parse fine but would make the robot panic or behave nonsensically
+    fn validate(&self) -> Result<(), String> {
+        for (name, value) in [
+            ("sell_ratio", self.sell_ratio),
+            ("gather_ratio", self.gather_ratio),
+            ("pickup_chance", self.pickup_chance),
+            ("spyglass_chance", self.spyglass_chance),
+            ("end_coverage", self.end_coverage),
+        ] {
+            if !(0.0..=1.0).contains(&value) {
+                return Err(format!("{name} must be between 0 and 1, not {value}"));
+            }
+        }
+        if self.gather_ratio > self.sell_ratio {
+            return Err(format!(
+                "gather_ratio ({}) can't be more than sell_ratio ({})",
+                self.gather_ratio, self.sell_ratio
+            ));
+        }
+        // otherwise the robot would be low on energy again as soon as it's done charging
+        if self.recharge_to <= self.low_energy {
+            return Err(format!(
+                "recharge_to ({}) must be more than low_energy ({})",
+                self.recharge_to, self.low_energy
+            ));
+        }
+        if self.exploring_spyglass_divisor == 0 {
+            return Err("exploring_spyglass_divisor can't be 0".to_string());
+        }
+        Ok(())
+    }
+
+    // amount of content in the backpack over which the robot should go selling
+    pub fn sell_threshold(&self, backpack_size: usize) -> usize {
+        (backpack_size as f32 * self.sell_ratio) as usize
+    }
+
+    // amount of content in the backpack under which the robot should go gathering
+    pub fn gather_threshold(&self, backpack_size: usize) -> usize {
+        (backpack_size as f32 * self.gather_ratio) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_is_valid() {
+        assert!(PioneerConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn recharge_must_be_over_low_energy() {
+        let config = PioneerConfig {
+            low_energy: 300,
+            recharge_to: 300,
+            ..PioneerConfig::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn ratios_are_checked() {
+        let config = PioneerConfig {
+            sell_ratio: 1.5,
+            ..PioneerConfig::default()
+        };
+        assert!(config.validate().is_err());
+        let config = PioneerConfig {
+            gather_ratio: 0.9,
+            sell_ratio: 0.5,
+            ..PioneerConfig::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn parsed_values_are_validated() {
+        let config = toml::from_str::<PioneerConfig>("exploring_spyglass_divisor = 0").unwrap();
+        assert!(config.validate().is_err());
+    }
+}
@@ -0,0 +1,69 @@
+use std::fmt::{Display, Formatter};
+
+use rustbeef_nlacompass::compass::Destination;
+
+// NLA compass, spyglass and robotics_lib all use (row, column) to index the map
+// while tile/resource mapper does the opposite, so the two get their own types:
+// coordinates coming from the mapper are XY and need to be converted before being used anywhere else
+
+// (row, column) coordinates, the ones used by the robot
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RowCol {
+    pub row: usize,
+    pub col: usize,
+}
+
+// (x, y) coordinates, the ones used by the tile/resource mapper
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct XY {
+    pub x: usize,
+    pub y: usize,
+}
+
+impl RowCol {
+    pub fn new(row: usize, col: usize) -> Self {
+        RowCol { row, col }
+    }
+
+    pub fn distance(&self, other: RowCol) -> usize {
+        self.row.abs_diff(other.row) + self.col.abs_diff(other.col)
+    }
+}
+
+impl XY {
+    // coordinates as returned by the mapper, which converts them into (x, y) tuples
+    pub fn from_mapper((x, y): (usize, usize)) -> Self {
+        XY { x, y }
+    }
+}
+
+impl From<XY> for RowCol {
+    fn from(c: XY) -> Self {
+        RowCol { row: c.y, col: c.x }
+    }
+}
+
+impl From<RowCol> for XY {
+    fn from(c: RowCol) -> Self {
+        XY { x: c.col, y: c.row }
+    }
+}
+
+// robotics_lib, the spyglass and the gui take plain (row, column) tuples
+impl From<RowCol> for (usize, usize) {
+    fn from(c: RowCol) -> Self {
+        (c.row, c.col)
+    }
+}
+
+impl From<RowCol> for Destination {
+    fn from(c: RowCol) -> Self {
+        Destination::Coordinate((c.row, c.col))
+    }
+}
+
+impl Display for RowCol {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({}, {})", self.row, self.col)
+    }
+}
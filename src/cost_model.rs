@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+use std::mem::{self, Discriminant};
+
+use robotics_lib::world::environmental_conditions::WeatherType;
+use robotics_lib::world::tile::{Tile, TileType};
+
+use crate::strategy::STEP_ENERGY;
+
+// energy the robot actually spends for each step, learned from the EnergyConsumed events of every go
+// it depends on the tile stepped on, on the elevation difference and on the weather,
+// so until a kind of step has been seen enough times a fixed estimate is used instead
+
+// steps of a kind needed before what was learned replaces the estimate
+const MIN_SAMPLES: usize = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct Key {
+    tile_type: Discriminant<TileType>,
+    // elevation of the tile stepped on minus the one of the tile left
+    delta: i64,
+    weather: Discriminant<WeatherType>,
+}
+
+pub struct CostModel {
+    // total energy spent and number of steps for each kind of step
+    samples: HashMap<Key, (usize, usize)>,
+    weather: WeatherType,
+}
+
+// estimated energy needed to walk on a tile type, None if it can't be walked on
+fn walk_cost(tile_type: &TileType) -> Option<usize> {
+    match tile_type {
+        | TileType::Street => Some(1),
+        | TileType::Grass | TileType::Teleport(_) => Some(2),
+        | TileType::Sand => Some(3),
+        | TileType::ShallowWater | TileType::Snow => Some(4),
+        | TileType::Hill => Some(5),
+        | TileType::Mountain => Some(8),
+        | TileType::DeepWater | TileType::Lava | TileType::Wall => None,
+    }
+}
+
+impl Default for CostModel {
+    fn default() -> Self {
+        CostModel {
+            samples: HashMap::new(),
+            weather: WeatherType::Sunny,
+        }
+    }
+}
+
+impl CostModel {
+    pub fn new() -> Self {
+        CostModel::default()
+    }
+
+    // steps are learned and estimated for the weather the robot is in right now
+    pub fn set_weather(&mut self, weather: WeatherType) {
+        self.weather = weather;
+    }
+
+    fn key(&self, from: Option<&Tile>, to: &Tile) -> Key {
+        Key {
+            tile_type: mem::discriminant(&to.tile_type),
+            delta: from.map(|from| to.elevation as i64 - from.elevation as i64).unwrap_or(0),
+            weather: mem::discriminant(&self.weather),
+        }
+    }
+
+    pub fn learn(&mut self, from: &Tile, to: &Tile, energy: usize) {
+        let key = self.key(Some(from), to);
+        let (total, steps) = self.samples.entry(key).or_insert((0, 0));
+        *total += energy;
+        *steps += 1;
+    }
+
+    // energy it takes to step from a tile to the next one, None if the next one can't be walked on
+    // (never less than 1, so that the pathfinder can still use the manhattan distance)
+    pub fn step(&self, from: Option<&Tile>, to: &Tile) -> Option<usize> {
+        let estimate = walk_cost(&to.tile_type)?;
+        match self.samples.get(&self.key(from, to)) {
+            | Some((total, steps)) if *steps >= MIN_SAMPLES => Some((total / steps).max(1)),
+            | _ => {
+                let climb = from.map(|from| to.elevation.saturating_sub(from.elevation)).unwrap_or(0);
+                Some(estimate + climb.pow(2))
+            }
+        }
+    }
+
+    // average energy of a step in the current weather, used to turn distances into energy
+    // when the tiles in between aren't known
+    pub fn average_step(&self) -> f32 {
+        let weather = mem::discriminant(&self.weather);
+        let (total, steps) = self
+            .samples
+            .iter()
+            .filter(|(key, _)| key.weather == weather)
+            .fold((0, 0), |(total, steps), (_, (t, s))| (total + t, steps + s));
+        match steps {
+            | 0 => STEP_ENERGY,
+            | _ => total as f32 / steps as f32,
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+
+use robotics_lib::world::tile::Tile;
+
+use crate::coords::RowCol;
+use crate::cost_model::CostModel;
+use crate::pathfinder;
+
+// exploration heads for frontier cells: known tiles the robot can stand on, right next to tiles it hasn't discovered yet
+// (unlike the centre of an unexplored area, they are never in the middle of a lake)
+
+// undiscovered tiles this close to a frontier count towards what the robot would find out there
+const GAIN_RADIUS: usize = 2;
+// most promising frontiers (by distance alone) for which a route is actually looked for
+const CANDIDATES: usize = 10;
+
+pub struct Frontier {
+    pub cell: RowCol,
+    // undiscovered tiles around it
+    pub gain: usize,
+    // estimated energy to get there
+    pub cost: usize,
+}
+
+impl Frontier {
+    // undiscovered tiles for each unit of energy, with far away frontiers penalized further
+    // since the routes to them go through more tiles the robot can only guess about
+    fn score(&self, from: RowCol) -> f32 {
+        self.gain as f32 / (1 + self.cost + from.distance(self.cell)) as f32
+    }
+}
+
+fn unknown_around(map: &[Vec<Option<Tile>>], cell: RowCol, radius: usize) -> usize {
+    let rows = cell.row.saturating_sub(radius)..=(cell.row + radius).min(map.len() - 1);
+    rows.map(|row| {
+        let cols = cell.col.saturating_sub(radius)..=(cell.col + radius).min(map[row].len() - 1);
+        cols.filter(|col| map[row][*col].is_none()).count()
+    })
+    .sum()
+}
+
+// best frontier to explore from where the robot is, leaving out the cells to skip
+// (the ones it already failed to reach) and the ones that cost more than max_cost to get to
+pub fn best(
+    map: &[Vec<Option<Tile>>],
+    teleports: &BTreeSet<RowCol>,
+    costs: &CostModel,
+    from: RowCol,
+    skip: impl Fn(RowCol) -> bool,
+    max_cost: usize,
+) -> Option<Frontier> {
+    let mut cells = Vec::new();
+    for (row, tiles) in map.iter().enumerate() {
+        for (col, tile) in tiles.iter().enumerate() {
+            let Some(tile) = tile else {
+                continue;
+            };
+            let cell = RowCol::new(row, col);
+            if cell == from || costs.step(None, tile).is_none() || skip(cell) {
+                continue;
+            }
+            // the robot sees the tiles around it, so diagonal ones count as well
+            if unknown_around(map, cell, 1) > 0 {
+                cells.push((cell, unknown_around(map, cell, GAIN_RADIUS)));
+            }
+        }
+    }
+
+    let promise = |(cell, gain): &(RowCol, usize)| *gain as f32 / (1 + from.distance(*cell)) as f32;
+    cells.sort_by(|a, b| promise(b).partial_cmp(&promise(a)).unwrap_or(Ordering::Equal));
+
+    cells
+        .into_iter()
+        .take(CANDIDATES)
+        .filter_map(|(cell, gain)| {
+            let cost = pathfinder::route(map, teleports, costs, from, cell)?.cost;
+            (cost <= max_cost).then_some(Frontier { cell, gain, cost })
+        })
+        .max_by(|a, b| a.score(from).partial_cmp(&b.score(from)).unwrap_or(Ordering::Equal))
+}
@@ -0,0 +1,158 @@
+use std::collections::BTreeMap;
+use std::mem::{self, Discriminant};
+
+use robotics_lib::world::tile::{Content, Tile};
+
+use crate::config::PioneerConfig;
+use crate::coords::RowCol;
+
+// markets and banks can only take so much: this keeps track of how much each known one has left,
+// from what the known map shows and from what every put into it actually got accepted,
+// so that the robot heads to one that can take the whole load, and partly used ones
+// are still good for smaller loads
+
+struct Entry {
+    kind: Discriminant<Content>,
+    // capacity the known map showed the last time it was looked at
+    seen: usize,
+    // capacity left as far as the robot knows
+    left: usize,
+    // day it was last updated
+    day: usize,
+}
+
+pub struct CapacityLedger {
+    // ordered, so that ties between markets or banks are broken the same way on every run
+    entries: BTreeMap<RowCol, Entry>,
+    // days after which an exhausted market or bank is worth another try
+    restock_days: usize,
+}
+
+// how much a market or bank tile can still take
+pub fn capacity(content: &Content) -> Option<usize> {
+    match content {
+        | Content::Market(n) => Some(*n),
+        | Content::Bank(range) => Some(range.end.saturating_sub(range.start)),
+        | _ => None,
+    }
+}
+
+impl CapacityLedger {
+    pub fn new(config: &PioneerConfig) -> Self {
+        CapacityLedger {
+            entries: BTreeMap::new(),
+            restock_days: config.market_restock_days,
+        }
+    }
+
+    // takes in the markets and banks of the known map
+    // a tile only overrides what the transactions said once it shows something different,
+    // since the known map isn't updated until the robot looks at the tile again
+    pub fn observe(&mut self, map: &[Vec<Option<Tile>>], day: usize) {
+        for (row, tiles) in map.iter().enumerate() {
+            for (col, tile) in tiles.iter().enumerate() {
+                if let Some(tile) = tile {
+                    self.observe_tile(RowCol::new(row, col), tile, day);
+                }
+            }
+        }
+    }
+
+    fn observe_tile(&mut self, c: RowCol, tile: &Tile, day: usize) {
+        let Some(seen) = capacity(&tile.content) else {
+            return;
+        };
+        let entry = self.entries.entry(c).or_insert(Entry {
+            kind: mem::discriminant(&tile.content),
+            seen,
+            left: seen,
+            day,
+        });
+        if entry.seen != seen {
+            entry.seen = seen;
+            entry.left = seen;
+            entry.day = day;
+        }
+    }
+
+    // a put into the market or bank got some of the load accepted:
+    // if it didn't take everything, there is no room left
+    // the known map must have been observed before the put, or the put would be counted twice
+    // once the map shows the tile after it
+    pub fn transaction(&mut self, c: RowCol, requested: usize, accepted: usize, day: usize) {
+        let Some(entry) = self.entries.get_mut(&c) else {
+            return;
+        };
+        entry.left = if accepted < requested { 0 } else { entry.left.saturating_sub(accepted) };
+        entry.day = day;
+        println!("{c} took {accepted} of {requested}, {} left", entry.left);
+    }
+
+    // capacity left, None if it's unknown or if it ran out long enough ago to be worth another try
+    pub fn left(&self, c: RowCol, day: usize) -> Option<usize> {
+        let entry = self.entries.get(&c)?;
+        match entry.left {
+            | 0 if entry.day + self.restock_days <= day => None,
+            | left => Some(left),
+        }
+    }
+
+    pub fn is_exhausted(&self, c: RowCol, day: usize) -> bool {
+        self.left(c, day) == Some(0)
+    }
+
+    // smallest load a market or bank the robot comes across should be able to take:
+    // the whole load if a known one can, otherwise anything at all
+    pub fn room_needed(&self, content: &Content, load: usize, day: usize, skip: impl Fn(RowCol) -> bool) -> usize {
+        let kind = mem::discriminant(content);
+        let fits = self
+            .entries
+            .iter()
+            .filter(|(c, entry)| entry.kind == kind && !skip(**c))
+            .any(|(c, _)| self.left(*c, day).map(|left| left >= load).unwrap_or(true));
+        if fits { load.max(1) } else { 1 }
+    }
+
+    // up to n markets or banks that can take the whole load (or whose room is unknown), closest first
+    pub fn with_room(
+        &self,
+        content: &Content,
+        from: RowCol,
+        load: usize,
+        day: usize,
+        n: usize,
+        skip: impl Fn(RowCol) -> bool,
+    ) -> Vec<RowCol> {
+        let kind = mem::discriminant(content);
+        let mut found = self
+            .entries
+            .iter()
+            .filter(|(c, entry)| entry.kind == kind && !skip(**c))
+            .map(|(c, _)| *c)
+            .filter(|c| self.left(*c, day).map(|left| left >= load).unwrap_or(true))
+            .collect::<Vec<_>>();
+        found.sort_by_key(|c| from.distance(*c));
+        found.truncate(n);
+        found
+    }
+
+    // market or bank to head to with a load: the closest one that can take all of it,
+    // otherwise the one with the most room left
+    pub fn best(&self, content: &Content, from: RowCol, load: usize, day: usize, skip: impl Fn(RowCol) -> bool) -> Option<RowCol> {
+        let kind = mem::discriminant(content);
+        let candidates = self
+            .entries
+            .iter()
+            .filter(|(c, entry)| entry.kind == kind && !skip(**c))
+            .map(|(c, _)| (*c, self.left(*c, day)))
+            .filter(|(_, left)| *left != Some(0))
+            .collect::<Vec<_>>();
+
+        candidates
+            .iter()
+            .filter(|(_, left)| left.map(|left| left >= load).unwrap_or(true))
+            .min_by_key(|(c, _)| from.distance(*c))
+            .or_else(|| candidates.iter().max_by_key(|(_, left)| *left))
+            .map(|(c, _)| *c)
+    }
+}
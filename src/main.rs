@@ -1,7 +1,26 @@
+mod config;
+mod coords;
+mod cost_model;
+mod frontier;
+mod ledger;
+mod memory;
+mod objective;
+mod pathfinder;
 mod pilot;
 mod pioneer_bot;
+mod planner;
+mod prices;
+mod settlement;
+mod strategy;
+mod stuck;
+mod tasks;
+mod tour;
+mod trace;
+mod verified_spyglass;
+mod visits;
 
 use std::rc::Rc;
+use config::PioneerConfig;
 use pioneer_bot::PioneerBot;
 use robotics_lib::runner::Runner;
 use worldgen_unwrap::public::WorldgeneratorUnwrap;
@@ -10,10 +29,32 @@ use worldgen_unwrap::public::WorldgeneratorUnwrap;
 const USE_WORLD_GEN_GUI: bool = false;
 const USE_GAME_GUI: bool = true;
 const USE_SOUND: bool = true;
+// set to None to disable the trace of the decisions
+const TRACE_FILE: Option<&str> = Some("decisions.jsonl");
 
 
 fn main() {
-    let pioneer_bot = PioneerBot::new(USE_GAME_GUI, USE_SOUND);
+    // the tuning parameters can be passed as a TOML file, eg. `cargo run -- pioneer.toml`
+    let config = match std::env::args().nth(1) {
+        | Some(path) => match PioneerConfig::load(&path) {
+            | Ok(config) => config,
+            | Err(e) => {
+                eprintln!("{e}");
+                return;
+            }
+        },
+        | None => PioneerConfig::default(),
+    };
+
+    let strategy = match strategy::by_name(&config.strategy) {
+        | Some(strategy) => strategy,
+        | None => {
+            eprintln!("Unknown strategy {}", config.strategy);
+            return;
+        }
+    };
+
+    let pioneer_bot = PioneerBot::new(USE_GAME_GUI, USE_SOUND, strategy, TRACE_FILE, config);
     let mut world_generator = WorldgeneratorUnwrap::init(USE_WORLD_GEN_GUI, Some(std::path::PathBuf::from("world\\test_world")));
     let _continue_ = Rc::clone(&pioneer_bot.running);
     if let Ok(mut runner) = Runner::new(Box::new(pioneer_bot), &mut world_generator) {
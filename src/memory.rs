@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::mem;
+
+use robotics_lib::world::tile::{Content, Tile};
+
+use crate::config::PioneerConfig;
+use crate::coords::RowCol;
+
+// why the robot should stay away from a target for a while
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Avoid {
+    // the robot couldn't get there
+    Unreachable,
+    // the spyglass reported content that wasn't there
+    FalsePositive,
+    // the content was gone by the time the robot got there
+    Vanished,
+}
+
+impl Display for Avoid {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            | Avoid::Unreachable => write!(f, "unreachable"),
+            | Avoid::FalsePositive => write!(f, "a false positive"),
+            | Avoid::Vanished => write!(f, "vanished"),
+        }
+    }
+}
+
+struct Entry {
+    reason: Avoid,
+    // first day the target is considered again
+    until: usize,
+}
+
+// targets the robot should not be sent to, each one forgotten after some days
+// since paths get built and content respawns
+pub struct TargetMemory {
+    entries: HashMap<RowCol, Entry>,
+    unreachable_days: usize,
+    false_positive_days: usize,
+    vanished_days: usize,
+}
+
+impl TargetMemory {
+    pub fn new(config: &PioneerConfig) -> Self {
+        TargetMemory {
+            entries: HashMap::new(),
+            unreachable_days: config.unreachable_expiry_days,
+            false_positive_days: config.false_positive_expiry_days,
+            vanished_days: config.vanished_expiry_days,
+        }
+    }
+
+    pub fn remember(&mut self, target: RowCol, reason: Avoid, day: usize) {
+        let days = match reason {
+            | Avoid::Unreachable => self.unreachable_days,
+            | Avoid::FalsePositive => self.false_positive_days,
+            | Avoid::Vanished => self.vanished_days,
+        };
+        println!("{target} is {reason}, avoiding it for {days} days");
+        self.entries.insert(target, Entry { reason, until: day + days });
+    }
+
+    pub fn avoids(&self, target: RowCol, day: usize) -> bool {
+        self.entries.get(&target).map(|entry| entry.until > day).unwrap_or(false)
+    }
+
+    // whether the target is avoided for that specific reason
+    pub fn avoids_because(&self, target: RowCol, reason: Avoid, day: usize) -> bool {
+        self.entries.get(&target).map(|entry| entry.reason == reason && entry.until > day).unwrap_or(false)
+    }
+
+    pub fn forget_expired(&mut self, day: usize) {
+        self.entries.retain(|_, entry| entry.until > day);
+    }
+
+    // closest tile of the known map holding the content, skipping the avoided ones and the ones the caller excludes
+    pub fn closest(
+        &self,
+        map: &[Vec<Option<Tile>>],
+        from: RowCol,
+        content: &Content,
+        day: usize,
+        exclude: impl Fn(RowCol) -> bool,
+    ) -> Option<RowCol> {
+        self.candidates(map, content, day)
+            .filter(|(c, _)| !exclude(*c))
+            .min_by_key(|(c, _)| from.distance(*c))
+            .map(|(c, _)| c)
+    }
+
+    // tile of the known map holding the most of the content, skipping the avoided ones and the ones the caller excludes
+    pub fn most_loaded(
+        &self,
+        map: &[Vec<Option<Tile>>],
+        content: &Content,
+        day: usize,
+        exclude: impl Fn(RowCol) -> bool,
+    ) -> Option<RowCol> {
+        self.candidates(map, content, day)
+            .filter(|(c, _)| !exclude(*c))
+            .max_by_key(|(_, amount)| *amount)
+            .map(|(c, _)| c)
+    }
+
+    fn candidates<'a>(
+        &'a self,
+        map: &'a [Vec<Option<Tile>>],
+        content: &'a Content,
+        day: usize,
+    ) -> impl Iterator<Item = (RowCol, usize)> + 'a {
+        map.iter().enumerate().flat_map(move |(row, tiles)| {
+            tiles.iter().enumerate().filter_map(move |(col, tile)| {
+                let tile = tile.as_ref()?;
+                let c = RowCol::new(row, col);
+                if mem::discriminant(&tile.content) != mem::discriminant(content) || self.avoids(c, day) {
+                    return None;
+                }
+                // contents that come in amounts are only worth going to if there is some left
+                match amount(&tile.content) {
+                    | Some(0) => None,
+                    | Some(amount) => Some((c, amount)),
+                    | None => Some((c, 0)),
+                }
+            })
+        })
+    }
+}
+
+fn amount(content: &Content) -> Option<usize> {
+    match content {
+        | Content::Rock(n)
+        | Content::Tree(n)
+        | Content::Garbage(n)
+        | Content::Coin(n)
+        | Content::Water(n)
+        | Content::Market(n)
+        | Content::Fish(n)
+        | Content::Bush(n)
+        | Content::JollyBlock(n) => Some(*n),
+        | Content::Bin(range) | Content::Crate(range) | Content::Bank(range) => Some(range.end.saturating_sub(range.start)),
+        | Content::Fire | Content::Building | Content::Scarecrow | Content::None => None,
+    }
+}
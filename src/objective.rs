@@ -0,0 +1,206 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use robotics_lib::world::environmental_conditions::DayTime;
+use robotics_lib::world::tile::Content;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::objective::Objective::{
+    Charging, Crafting, Depositing, Exploring, Gathering, Moving, Praying, Selling, Sleeping, Waiting,
+};
+
+// Possible states of the robot
+#[derive(Clone, Debug, PartialEq)]
+pub enum Objective {
+    Waiting(DayTime),
+    Moving(bool),
+    Charging(usize),
+    Sleeping,
+    Praying,
+    Gathering(Content),
+    Selling(Content),
+    Crafting(Content),
+    Depositing,
+    Exploring,
+    None,
+}
+
+// the text form of an objective is its name, followed by its parameter in brackets if it has one
+// eg. "waiting(night)", "moving(true)", "charging(750)", "gathering(fish)", "selling(rock(3))", "depositing"
+// it's the same form used in the logs, the trace and anywhere an objective needs to be named,
+// and it can be parsed back into the same objective
+impl Display for Objective {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            | Waiting(d) => write!(f, "waiting({})", day_time_name(d)),
+            | Moving(discover_new) => write!(f, "moving({discover_new})"),
+            | Charging(n) => write!(f, "charging({n})"),
+            | Sleeping => write!(f, "sleeping"),
+            | Praying => write!(f, "praying"),
+            | Gathering(content) => write!(f, "gathering({})", content_name(content)),
+            | Selling(content) => write!(f, "selling({})", content_name(content)),
+            | Crafting(content) => write!(f, "crafting({})", content_name(content)),
+            | Depositing => write!(f, "depositing"),
+            | Exploring => write!(f, "exploring"),
+            | Objective::None => write!(f, "none"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ParseObjectiveError(String);
+
+impl Display for ParseObjectiveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid objective: {}", self.0)
+    }
+}
+
+impl FromStr for Objective {
+    type Err = ParseObjectiveError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParseObjectiveError(s.to_string());
+        let (name, parameter) = split_parameter(s.trim()).ok_or_else(error)?;
+
+        match (name, parameter) {
+            | ("waiting", Some(d)) => parse_day_time(d).map(Waiting).ok_or_else(error),
+            | ("moving", Some(discover_new)) => discover_new.parse().map(Moving).map_err(|_| error()),
+            | ("charging", Some(n)) => n.parse().map(Charging).map_err(|_| error()),
+            | ("sleeping", None) => Ok(Sleeping),
+            | ("praying", None) => Ok(Praying),
+            | ("gathering", Some(content)) => parse_content(content).map(Gathering).ok_or_else(error),
+            | ("selling", Some(content)) => parse_content(content).map(Selling).ok_or_else(error),
+            | ("crafting", Some(content)) => parse_content(content).map(Crafting).ok_or_else(error),
+            | ("depositing", None) => Ok(Depositing),
+            | ("exploring", None) => Ok(Exploring),
+            | ("none", None) => Ok(Objective::None),
+            | _ => Err(error()),
+        }
+    }
+}
+
+// objectives are (de)serialized as their text form
+impl Serialize for Objective {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Objective {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+// converts the u8 readings from the serial port to
+// the corresponding objective
+impl From<u8> for Objective {
+    fn from(value: u8) -> Self {
+        match value {
+            | 1 => Charging(750),
+            | 2 => Selling(Content::Fish(0)),
+            | 3 => Selling(Content::Tree(0)),
+            | 4 => Selling(Content::Rock(0)),
+            | 5 => Gathering(Content::Fish(0)),
+            | 6 => Gathering(Content::Tree(0)),
+            | 7 => Gathering(Content::Rock(0)),
+            | 8 => Depositing,
+            | 9 => Exploring,
+            | _ => Objective::None,
+        }
+    }
+}
+
+// splits "name(parameter)" into its two parts, the parameter being optional
+fn split_parameter(s: &str) -> Option<(&str, Option<&str>)> {
+    match s.find('(') {
+        | Some(i) => s[i + 1..].strip_suffix(')').map(|parameter| (&s[..i], Some(parameter))),
+        | None => Some((s, None)),
+    }
+}
+
+fn day_time_name(d: &DayTime) -> &'static str {
+    match d {
+        | DayTime::Morning => "morning",
+        | DayTime::Afternoon => "afternoon",
+        | DayTime::Night => "night",
+    }
+}
+
+fn parse_day_time(s: &str) -> Option<DayTime> {
+    match s {
+        | "morning" => Some(DayTime::Morning),
+        | "afternoon" => Some(DayTime::Afternoon),
+        | "night" => Some(DayTime::Night),
+        | _ => None,
+    }
+}
+
+// name of the content, followed by its value when it isn't the default one
+// eg. "fish", "rock(3)", "bank(0..10)"
+pub(crate) fn content_name(content: &Content) -> String {
+    let (name, value) = match content {
+        | Content::Rock(n) => ("rock", amount(*n)),
+        | Content::Tree(n) => ("tree", amount(*n)),
+        | Content::Garbage(n) => ("garbage", amount(*n)),
+        | Content::Fire => ("fire", None),
+        | Content::Coin(n) => ("coin", amount(*n)),
+        | Content::Bin(range) => ("bin", interval(range)),
+        | Content::Crate(range) => ("crate", interval(range)),
+        | Content::Bank(range) => ("bank", interval(range)),
+        | Content::Water(n) => ("water", amount(*n)),
+        | Content::Market(n) => ("market", amount(*n)),
+        | Content::Fish(n) => ("fish", amount(*n)),
+        | Content::Building => ("building", None),
+        | Content::Bush(n) => ("bush", amount(*n)),
+        | Content::JollyBlock(n) => ("tent", amount(*n)),
+        | Content::Scarecrow => ("scarecrow", None),
+        | Content::None => ("nothing", None),
+    };
+    match value {
+        | Some(value) => format!("{name}({value})"),
+        | None => name.to_string(),
+    }
+}
+
+pub(crate) fn parse_content(s: &str) -> Option<Content> {
+    let (name, value) = split_parameter(s)?;
+    let n = || value.map(|v| v.parse::<usize>().ok()).unwrap_or(Some(0));
+    let range = || match value {
+        | Some(v) => {
+            let (start, end) = v.split_once("..")?;
+            Some(start.parse().ok()?..end.parse().ok()?)
+        }
+        | None => Some(0..0),
+    };
+    match name {
+        | "rock" => n().map(Content::Rock),
+        | "tree" => n().map(Content::Tree),
+        | "garbage" => n().map(Content::Garbage),
+        | "fire" if value.is_none() => Some(Content::Fire),
+        | "coin" => n().map(Content::Coin),
+        | "bin" => range().map(Content::Bin),
+        | "crate" => range().map(Content::Crate),
+        | "bank" => range().map(Content::Bank),
+        | "water" => n().map(Content::Water),
+        | "market" => n().map(Content::Market),
+        | "fish" => n().map(Content::Fish),
+        | "building" if value.is_none() => Some(Content::Building),
+        | "bush" => n().map(Content::Bush),
+        | "tent" => n().map(Content::JollyBlock),
+        | "scarecrow" if value.is_none() => Some(Content::Scarecrow),
+        | "nothing" if value.is_none() => Some(Content::None),
+        | _ => None,
+    }
+}
+
+fn amount(n: usize) -> Option<String> {
+    if n == 0 { None } else { Some(n.to_string()) }
+}
+
+fn interval(range: &std::ops::Range<usize>) -> Option<String> {
+    if range.start == 0 && range.end == 0 { None } else { Some(format!("{}..{}", range.start, range.end)) }
+}
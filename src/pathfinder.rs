@@ -0,0 +1,238 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, BinaryHeap, HashMap};
+
+use robotics_lib::interface::Direction;
+use robotics_lib::world::tile::Tile;
+
+use crate::coords::RowCol;
+use crate::cost_model::CostModel;
+
+// A* over the map known by the robot, used when the compass gets lost or keeps going back and forth,
+// and to estimate how much energy it takes to get somewhere
+// tiles that haven't been discovered yet are assumed to be as cheap as they can be, so routes
+// through unknown areas are tried first and corrected as the robot finds out what's there
+// steps cost what the robot learned they cost (see cost_model.rs),
+// teleports the robot knows about are linked to each other, so routes can jump between them
+
+// cost given to tiles that haven't been discovered yet
+const UNKNOWN_COST: usize = 1;
+// the search gives up after looking at this many tiles
+const MAX_EXPANSIONS: usize = 50_000;
+// energy taken by robotics_lib to teleport
+pub(crate) const TELEPORT_ENERGY: usize = 30;
+
+// cost of stepping from a tile to the next one, according to what the robot learned so far
+fn step_cost(costs: &CostModel, from: &Option<Tile>, to: &Option<Tile>) -> Option<usize> {
+    match to {
+        | Some(tile) => costs.step(from.as_ref(), tile),
+        | None => Some(UNKNOWN_COST),
+    }
+}
+
+fn neighbours(map: &[Vec<Option<Tile>>], (row, col): (usize, usize)) -> Vec<(Direction, (usize, usize))> {
+    let mut neighbours = Vec::new();
+    if row > 0 {
+        neighbours.push((Direction::Up, (row - 1, col)));
+    }
+    if row + 1 < map.len() {
+        neighbours.push((Direction::Down, (row + 1, col)));
+    }
+    if col > 0 {
+        neighbours.push((Direction::Left, (row, col - 1)));
+    }
+    if col + 1 < map[row].len() {
+        neighbours.push((Direction::Right, (row, col + 1)));
+    }
+    neighbours
+}
+
+#[derive(Clone, Debug)]
+pub enum Step {
+    Go(Direction),
+    // from the teleport the robot is standing on to another one
+    Teleport(RowCol),
+}
+
+pub struct Route {
+    // steps to follow, in order
+    pub steps: Vec<Step>,
+    // estimated energy it takes to follow them
+    pub cost: usize,
+}
+
+// cheapest route from start to goal, teleports being the ones the robot can use
+// the goal itself is always considered reachable, even if it can't be walked on
+// (eg. fish in deep water), the robot will deal with it once it's in front of it
+pub fn route(
+    map: &[Vec<Option<Tile>>],
+    teleports: &BTreeSet<RowCol>,
+    costs: &CostModel,
+    start: RowCol,
+    goal: RowCol,
+) -> Option<Route> {
+    let (start, goal): ((usize, usize), (usize, usize)) = (start.into(), goal.into());
+    if start.0 >= map.len() || start.1 >= map[start.0].len() || goal.0 >= map.len() || goal.1 >= map[goal.0].len() {
+        return None;
+    }
+
+    // the cheapest tiles cost 1, so the manhattan distance never overestimates,
+    // unless going through a teleport is shorter: walking to the closest one, jumping,
+    // and walking from the teleport closest to the goal is never more than that either
+    let teleports = teleports.iter().map(|t| (t.row, t.col)).collect::<Vec<_>>();
+    let manhattan = |a: (usize, usize), b: (usize, usize)| a.0.abs_diff(b.0) + a.1.abs_diff(b.1);
+    let from_teleports = teleports.iter().map(|t| manhattan(*t, goal)).min();
+    let heuristic = |position: (usize, usize)| {
+        let walking = manhattan(position, goal);
+        match (teleports.iter().map(|t| manhattan(position, *t)).min(), from_teleports) {
+            | (Some(to), Some(from)) if teleports.len() > 1 => walking.min(to + TELEPORT_ENERGY + from),
+            | _ => walking,
+        }
+    };
+
+    let mut best = HashMap::from([(start, 0usize)]);
+    let mut came_from: HashMap<(usize, usize), ((usize, usize), Step)> = HashMap::new();
+    let mut frontier = BinaryHeap::from([Reverse((heuristic(start), 0usize, start))]);
+    let mut expansions = 0;
+
+    while let Some(Reverse((_, cost, position))) = frontier.pop() {
+        if position == goal {
+            // walk back to the start to get the steps in order
+            let mut steps = Vec::new();
+            let mut current = position;
+            while let Some((previous, step)) = came_from.get(&current) {
+                steps.push(step.clone());
+                current = *previous;
+            }
+            steps.reverse();
+            return Some(Route { steps, cost });
+        }
+
+        // a cheaper way to this tile was found after it was queued
+        if best.get(&position).map(|c| *c < cost).unwrap_or(false) {
+            continue;
+        }
+
+        expansions += 1;
+        if expansions > MAX_EXPANSIONS {
+            return None;
+        }
+
+        let mut moves = Vec::new();
+        for (direction, next) in neighbours(map, position) {
+            match step_cost(costs, &map[position.0][position.1], &map[next.0][next.1]) {
+                | Some(step) => moves.push((Step::Go(direction), next, step)),
+                | None if next == goal => moves.push((Step::Go(direction), next, UNKNOWN_COST)),
+                | None => {}
+            }
+        }
+        if teleports.contains(&position) {
+            for next in teleports.iter().filter(|t| **t != position) {
+                moves.push((Step::Teleport(RowCol::new(next.0, next.1)), *next, TELEPORT_ENERGY));
+            }
+        }
+
+        for (step, next, energy) in moves {
+            let next_cost = cost + energy;
+            if best.get(&next).map(|c| next_cost < *c).unwrap_or(true) {
+                best.insert(next, next_cost);
+                came_from.insert(next, (position, step));
+                frontier.push(Reverse((next_cost + heuristic(next), next_cost, next)));
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use robotics_lib::interface::Direction;
+    use robotics_lib::world::tile::{Content, Tile, TileType};
+
+    use super::*;
+
+    fn tile(tile_type: TileType) -> Option<Tile> {
+        Some(Tile {
+            tile_type,
+            content: Content::None,
+            elevation: 0,
+        })
+    }
+
+    // a known map of streets, with walls where the pattern has a '#' and unknown tiles where it has a '?'
+    fn map(pattern: &[&str]) -> Vec<Vec<Option<Tile>>> {
+        pattern
+            .iter()
+            .map(|row| {
+                row.chars()
+                    .map(|c| match c {
+                        | '#' => tile(TileType::Wall),
+                        | '?' => None,
+                        | _ => tile(TileType::Street),
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn goes_around_a_wall() {
+        // the goal is right above the start, but the wall is open only at the bottom
+        let map = map(&[
+            ".....",
+            ".###.",
+            ".#.#.",
+            ".#.#.",
+            ".....",
+        ]);
+        let route = route(&map, &BTreeSet::new(), &CostModel::new(), RowCol::new(2, 2), RowCol::new(0, 2)).unwrap();
+        assert_eq!(route.steps.len(), 10);
+        assert_eq!(route.cost, 10);
+        assert!(matches!(route.steps[0], Step::Go(Direction::Down)));
+    }
+
+    #[test]
+    fn unknown_tiles_are_optimistic() {
+        // the unknown tile is cheaper than going around through the mountains
+        let mut map = map(&[".?."]);
+        map.push(vec![tile(TileType::Mountain), tile(TileType::Mountain), tile(TileType::Mountain)]);
+        let route = route(&map, &BTreeSet::new(), &CostModel::new(), RowCol::new(0, 0), RowCol::new(0, 2)).unwrap();
+        assert_eq!(route.steps.len(), 2);
+        assert_eq!(route.cost, UNKNOWN_COST + 1);
+        assert!(route.steps.iter().all(|step| matches!(step, Step::Go(Direction::Right))));
+    }
+
+    #[test]
+    fn walled_in_start_has_no_route() {
+        let map = map(&[
+            "..#.",
+            ".#.#",
+            "..#.",
+        ]);
+        assert!(route(&map, &BTreeSet::new(), &CostModel::new(), RowCol::new(1, 2), RowCol::new(0, 0)).is_none());
+    }
+
+    // a single row of streets with a teleport near each end
+    fn teleport_line() -> (Vec<Vec<Option<Tile>>>, BTreeSet<RowCol>) {
+        let mut row = map(&[".".repeat(40).as_str()]).remove(0);
+        row[1] = tile(TileType::Teleport(true));
+        row[38] = tile(TileType::Teleport(true));
+        (vec![row], BTreeSet::from([RowCol::new(0, 1), RowCol::new(0, 38)]))
+    }
+
+    #[test]
+    fn takes_the_teleport_when_walking_costs_more() {
+        let (map, teleports) = teleport_line();
+        let route = route(&map, &teleports, &CostModel::new(), RowCol::new(0, 0), RowCol::new(0, 39)).unwrap();
+        // walking would take 41
+        assert!(route.steps.iter().any(|step| matches!(step, Step::Teleport(c) if *c == RowCol::new(0, 38))));
+        assert_eq!(route.cost, 2 + TELEPORT_ENERGY + 1);
+    }
+
+    #[test]
+    fn walks_when_it_is_cheaper() {
+        let (map, teleports) = teleport_line();
+        let route = route(&map, &teleports, &CostModel::new(), RowCol::new(0, 0), RowCol::new(0, 10)).unwrap();
+        assert!(route.steps.iter().all(|step| matches!(step, Step::Go(_))));
+        assert_eq!(route.cost, 11);
+    }
+}
@@ -1,7 +1,47 @@
-use crate::pioneer_bot::Objective;
+use crate::objective::Objective;
 use serialport::{ErrorKind, SerialPort};
+use std::fmt::{Display, Formatter};
 use std::io::{Read, Write};
 
+// reasons why the objective chosen by the pilot can't be carried out,
+// sent back to the pico so that it can show them on the display
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Rejection {
+    NoFish,
+    NoWood,
+    NoRocks,
+    NoCoins,
+    NoMarket,
+    NoBank,
+    BackpackFull,
+    EnergyFull,
+}
+
+impl Rejection {
+    // rejections are sent as negative numbers from -10 downwards, so that they
+    // don't get mixed up with the score or with the other signals (-1 and -2)
+    fn code(self) -> f32 {
+        -10.0 - self as u8 as f32
+    }
+}
+
+// same messages shown by the pico
+impl Display for Rejection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            | Rejection::NoFish => "no fish in backpack",
+            | Rejection::NoWood => "no wood in backpack",
+            | Rejection::NoRocks => "no rocks in backpack",
+            | Rejection::NoCoins => "no coins in backpack",
+            | Rejection::NoMarket => "no known market",
+            | Rejection::NoBank => "no known bank",
+            | Rejection::BackpackFull => "backpack is full",
+            | Rejection::EnergyFull => "energy is full",
+        };
+        write!(f, "{message}")
+    }
+}
+
 // support struct in order to interface the main program with the raspberry pi pico
 pub struct Pilot {
     manual: bool,
@@ -46,6 +86,11 @@ impl Pilot {
         let _ = self.port.write(&score.to_le_bytes());
     }
 
+    // tell the pico why the objective it sent was rejected
+    pub(crate) fn put_rejection(&mut self, rejection: Rejection) {
+        let _ = self.port.write(&rejection.code().to_le_bytes());
+    }
+
     pub(crate) fn get_objective(&mut self) -> Result<Objective, ()> {
         // send the signal that an objective must be selected
         if let Ok(_) = self.port.write(&(-1.0f32).to_le_bytes()) {
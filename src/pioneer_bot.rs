@@ -1,7 +1,7 @@
-use rand::random;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use std::cell::RefCell;
-use std::collections::{HashMap, HashSet};
-use std::fmt::{Display, Formatter};
+use std::collections::{BTreeSet, HashMap};
 use std::mem;
 use std::rc::Rc;
 
@@ -10,13 +10,15 @@ use robotics_lib::utils::LibError;
 use robotics_lib::runner::Runnable;
 use robotics_lib::event::events::Event;
 use robotics_lib::interface::Direction::{Down, Left, Right, Up};
-use robotics_lib::interface::{craft, destroy, get_score, go, look_at_sky, put, robot_map, robot_view, Direction};
+use robotics_lib::interface::{
+    craft, destroy, get_score, go, look_at_sky, put, robot_map, robot_view, teleport, Direction,
+};
 
 use robotics_lib::runner::backpack::BackPack;
 use robotics_lib::runner::Robot;
 use robotics_lib::world::coordinates::Coordinate;
 use robotics_lib::world::environmental_conditions::{DayTime, WeatherType};
-use robotics_lib::world::tile::{Content, Tile};
+use robotics_lib::world::tile::{Content, Tile, TileType};
 use robotics_lib::world::World;
 
 use another_one_bytes_the_dust_tile_resource_mapper_tool::tool::tile_mapper::TileMapper as Map;
@@ -26,46 +28,37 @@ use pmp_collect_all::CollectAll;
 use rustbeef_nlacompass::compass::{Destination, MoveError, NLACompass as Compass};
 use spyglass::spyglass::*;
 
-use crate::pilot::Pilot;
-use crate::pioneer_bot::Objective::{Charging, Depositing, Exploring, Gathering, Moving, Praying, Selling, Sleeping, Waiting};
+use crate::config::PioneerConfig;
+use crate::coords::{RowCol, XY};
+use crate::cost_model::CostModel;
+use crate::frontier;
+use crate::ledger::{self, CapacityLedger};
+use crate::memory::{Avoid, TargetMemory};
+use crate::objective::{content_name, Objective};
+use crate::objective::Objective::{
+    Charging, Crafting, Depositing, Exploring, Gathering, Moving, Praying, Selling, Sleeping, Waiting,
+};
+use crate::pathfinder::{self, Step};
+use crate::pilot::{Pilot, Rejection};
+use crate::prices::PriceModel;
+use crate::settlement::Settlements;
+use crate::strategy::{content_to_sell, Snapshot, Strategy, MAX_ENERGY, SELLABLE};
+use crate::stuck::{Stuck, StuckDetector};
+use crate::tasks::{Task, TaskStack, TaskState};
+use crate::tour::{self, TourPlanner};
+use crate::verified_spyglass::VerifiedSpyglass;
+use crate::visits::{Outcome, VisitLog};
+use crate::trace::{Reason, Record, Trace};
 use colored::{Color, Colorize};
 use robo_gui::MainState;
 use robotics_lib::world::tile::Content::JollyBlock as Tent;
 
-// Possible states of the robot
-#[derive(Clone, Debug, PartialEq)]
-pub enum Objective {
-    Waiting(DayTime),
-    Moving(bool),
-    Charging(usize),
-    Sleeping,
-    Praying,
-    Gathering(Content),
-    Selling(Content),
-    Depositing,
-    Exploring,
-    None,
-}
+// rounds of steps taken when moving blindly, each one a step along the rows and one along the columns
+const BLIND_ROUNDS: usize = 4;
 
-impl Display for Objective {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self {
-                | Waiting(d) => format!("waiting till {:?}", d),
-                | Moving(_) => "moving".to_string(),
-                | Charging(n) => format!("charging to {}/1000", n),
-                | Sleeping => "sleeping".to_string(),
-                | Praying => "praying".to_string(),
-                | Gathering(_) => "gathering".to_string(),
-                | Selling(_) => "selling".to_string(),
-                | Depositing => "going to the bank".to_string(),
-                | Exploring => "exploring".to_string(),
-                | Objective::None => "doing nothing".to_string(),
-            }
-        )
-    }
+// number of discovered tiles in the known map
+fn known_tiles(map: &[Vec<Option<Tile>>]) -> usize {
+    map.iter().map(|row| row.iter().filter(|tile| tile.is_some()).count()).sum()
 }
 
 // main robot struct
@@ -77,19 +70,56 @@ pub struct PioneerBot<'a> {
 
     // current score, updated each tick
     score: f32,
-    // current and next objective set for the robot
-    objective: Objective,
-    next: Objective,
+    // objectives set for the robot, in the order they will be carried out
+    tasks: TaskStack,
+
+    // decides what to do during the Praying phase
+    strategy: Box<dyn Strategy>,
+    // tuning parameters
+    config: PioneerConfig,
+    // source of every random choice, so that runs can be replayed given the same world and seed
+    rng: StdRng,
+
+    // structured log of every change of objective
+    trace: Option<Trace>,
+    // ticks and days since the start, and current time of day
+    tick: usize,
+    day: usize,
+    time_of_day: DayTime,
 
     // Tile Resource mapper to keep track of content discovered
     map: Map,
 
-    // save some locations: ones that have already been explored and ones with depleted markets and banks
-    pins: HashSet<(usize, usize)>,
-    bankrupt: HashSet<(usize, usize)>,
+    // places the robot has been to and how it went, and the ones to stay away from for a while
+    // (unreachable targets, false positives of the spyglass and content that vanished)
+    visits: VisitLog,
+    memory: TargetMemory,
+    // how much each known market and bank can still take
+    ledger: CapacityLedger,
+    // towns found so far, and what it takes to travel between them
+    settlements: Settlements,
+    // spyglass results checked against the known map, and the destination the robot last reached
+    // to tell whether the content it came for is still there
+    spyglass: VerifiedSpyglass,
+    last_destination: Option<RowCol>,
+    // tiles in the known map the last time no frontier was left to explore
+    explored_at: Option<usize>,
+    // coins the markets gave for each content so far
+    prices: PriceModel,
 
     // NLA compass
     compass: Compass,
+    // route found by the pathfinder when the compass got lost or when a teleport makes for a shortcut,
+    // along with its destination (the steps are stored in reverse, so that the next one can be popped)
+    route: Option<(RowCol, Vec<Step>)>,
+    // active teleports found in the known map
+    teleports: BTreeSet<RowCol>,
+    // energy each kind of step takes, learned while moving
+    costs: CostModel,
+    // energy consumed since the last step started
+    step_energy: usize,
+    // tells when the robot is going nowhere while moving
+    stuck: StuckDetector,
     // oh_crab weather tool
     forecast: Forecast,
 
@@ -103,21 +133,63 @@ pub struct PioneerBot<'a> {
 }
 
 impl PioneerBot<'_> {
-    // initialize the robot, you can choose to use gui, audio or both from src/main.rs
-    pub fn new(gui_start: bool, audio_start: bool) -> Self {
+    // initialize the robot, you can choose to use gui, audio or both from src/main.rs,
+    // as well as the strategy used to decide the objective of the day
+    // the file in which to trace the decisions, if any, and the tuning parameters
+    pub fn new(
+        gui_start: bool,
+        audio_start: bool,
+        strategy: Box<dyn Strategy>,
+        trace_path: Option<&str>,
+        config: PioneerConfig,
+    ) -> Self {
+        let seed = config.seed.unwrap_or_else(rand::random);
+        println!("{}", format!("Seed: {seed}").color(Color::BrightWhite));
+        let stuck = StuckDetector::new(&config);
+        let memory = TargetMemory::new(&config);
+        let settlements = Settlements::new(&config);
+        let spyglass = VerifiedSpyglass::new(&config);
+        let visits = VisitLog::new(&config);
+        let ledger = CapacityLedger::new(&config);
+
         Self {
             robot: Default::default(),
             pilot: Pilot::new().ok(),
 
             score: 0.,
-            objective: Objective::None,
-            next: Objective::None,
+            tasks: TaskStack::default(),
+
+            strategy,
+            config,
+            rng: StdRng::seed_from_u64(seed),
+
+            trace: trace_path.and_then(|path| match Trace::create(path) {
+                | Ok(trace) => Some(trace),
+                | Err(e) => {
+                    eprintln!("Can't trace the decisions to {path}: {e}");
+                    None
+                }
+            }),
+            tick: 0,
+            day: 0,
+            time_of_day: DayTime::Morning,
 
             map: Map {},
-            pins: HashSet::new(),
-            bankrupt: HashSet::new(),
+            visits,
+            memory,
+            ledger,
+            settlements,
+            spyglass,
+            last_destination: None,
+            explored_at: None,
+            prices: PriceModel::new(),
 
             compass: Compass::new(),
+            route: None,
+            teleports: BTreeSet::new(),
+            costs: CostModel::new(),
+            step_energy: 0,
+            stuck,
             forecast: Forecast::new(),
 
             audio: if audio_start {
@@ -177,27 +249,215 @@ impl PioneerBot<'_> {
         (coordinate.get_row(), coordinate.get_col())
     }
 
-    // sets the objective for the robot and logs it in the terminal
-    pub(crate) fn set_objective(&mut self, objective: Objective) {
-        self.objective = objective;
+    fn position(&self) -> RowCol {
+        let coordinate = self.get_coordinate();
+        RowCol::new(coordinate.get_row(), coordinate.get_col())
+    }
+
+    // the tile/resource mapper works with (x, y) coordinates, these wrappers convert them right away
+    // the mapper doesn't know which targets the robot is avoiding, so when it suggests one of those
+    // the known map is searched for the next best one, leaving out the markets and banks that are full too
+    fn find_closest(&self, world: &mut World, content: Content) -> Option<RowCol> {
+        match self.map.find_closest(world, self, content.clone()).ok().map(|c| XY::from_mapper(c.into()).into()) {
+            | Some(c) if self.is_avoided(c) => {
+                let exhausted = |c| self.ledger.is_exhausted(c, self.day);
+                self.memory.closest(&robot_map(world)?, self.position(), &content, self.day, exhausted)
+            }
+            | c => c,
+        }
+    }
+
+    fn find_most_loaded(&self, world: &mut World, content: Content) -> Option<RowCol> {
+        match self.map.find_most_loaded(world, self, content.clone()).ok().map(|c| XY::from_mapper(c.into()).into()) {
+            | Some(c) if self.is_avoided(c) => {
+                let exhausted = |c| self.ledger.is_exhausted(c, self.day);
+                self.memory.most_loaded(&robot_map(world)?, &content, self.day, exhausted)
+            }
+            | c => c,
+        }
+    }
+
+    // replaces the current objective of the robot and logs it in the terminal
+    // the destination currently set in the compass is saved along with it, in case the task gets interrupted
+    pub(crate) fn set_objective(&mut self, objective: Objective, reason: Reason) {
+        let old = self.tasks.current();
+        let mut task = Task::new(objective);
+        if let Moving(_) = task.objective {
+            task.destination = self.compass_destination();
+        }
+        let distance = task.destination.map(|d| self.position().distance(d)).unwrap_or(0);
+        self.tasks.replace(task.with_budget(distance, &self.config));
+        self.announce_objective(old, reason);
+    }
+
+    // queues an objective right after the current one
+    fn set_next(&mut self, objective: Objective) {
+        self.tasks.queue(Task::new(objective).with_budget(0, &self.config))
+    }
+
+    // queues a trip to a destination right after the current objective, distance being
+    // how far it is from where the robot will be when it sets off
+    fn set_next_trip(&mut self, destination: RowCol, distance: usize) {
+        self.tasks.queue(
+            Task::new(Moving(true))
+                .with_destination(destination)
+                .with_budget(distance, &self.config),
+        )
+    }
+
+    // puts the current objective aside to take care of something more urgent first
+    fn interrupt(&mut self, objective: Objective, reason: Reason) {
+        let old = self.tasks.current();
+        println!("Putting {old} aside");
+        self.tasks.push(Task::new(objective));
+        self.announce_objective(old, reason);
+    }
+
+    // the current objective is done: resume the one that comes after,
+    // along with the destination it was heading to
+    fn next_objective(&mut self, reason: Reason) {
+        let old = self.tasks.current();
+        if let Some(Task { objective: Moving(_), destination, .. }) = self.tasks.pop() {
+            self.last_destination = destination;
+        }
+        if let Some(destination) = self.tasks.top().and_then(|task| task.destination) {
+            self.compass.set_destination(destination.into());
+        }
+        self.announce_objective(old, reason);
+    }
+
+    // logs the new objective in the terminal and in the trace
+    fn announce_objective(&mut self, old: Objective, reason: Reason) {
         if let (Some(audio), Some(sounds)) = (self.audio.as_mut(), self.sounds.as_ref()) {
             let _ = audio.play_audio(&sounds[0]);
         }
         println!(
             "{}",
-            format!("New objective: {}", self.objective).color(Color::BrightYellow)
+            format!("New objective: {}", self.tasks.current()).color(Color::BrightYellow)
         );
+
+        if let Some(trace) = self.trace.as_mut() {
+            trace.record(&Record {
+                tick: self.tick,
+                day: self.day,
+                time_of_day: format!("{:?}", self.time_of_day),
+                position: (self.robot.coordinate.get_row(), self.robot.coordinate.get_col()),
+                energy: self.robot.energy.get_energy_level(),
+                backpack: self
+                    .robot
+                    .backpack
+                    .get_contents()
+                    .iter()
+                    .filter(|(_, quantity)| **quantity > 0)
+                    .map(|(content, quantity)| (content_name(content), *quantity))
+                    .collect(),
+                old,
+                new: self.tasks.current(),
+                next: self.tasks.next(),
+                reason,
+            });
+        }
     }
 
-    // sets the next objective for the robot
-    fn set_next(&mut self, objective: Objective) {
-        self.next = objective
+    // gives up on the current task because it took too long, most likely because the robot got stuck,
+    // then decides what to do right away instead of waiting for the next morning
+    fn abandon_objective(&mut self, reason: Reason) {
+        if let Some(task) = self.tasks.pop() {
+            println!("{}", format!("Giving up on {}", task.objective).color(Color::BrightRed));
+            if let Some(destination) = task.destination {
+                self.memory.remember(destination, Avoid::Unreachable, self.day);
+            }
+            // the task waiting for the robot to get there has to go as well,
+            // or it would be resumed later on with no destination
+            if let Moving(_) = task.objective {
+                match self.tasks.current() {
+                    | Objective::None | Moving(_) => {}
+                    | dependent => {
+                        println!("{}", format!("Giving up on {dependent} as well").color(Color::BrightRed));
+                        self.tasks.pop();
+                    }
+                }
+            }
+        }
+        self.compass.clear_destination();
+        self.last_coords.clear();
+        self.stuck.clear();
+        self.interrupt(Praying, reason);
+    }
+
+    // whether a target should not be considered, either because it can't take
+    // any more content, because the robot couldn't reach it or because the content wasn't there
+    fn is_avoided(&self, c: RowCol) -> bool {
+        self.memory.avoids(c, self.day) || self.ledger.is_exhausted(c, self.day)
+    }
+
+    // market or bank to take a load to, after catching up with the known map
+    fn find_room(&mut self, world: &World, target: Content, load: usize) -> Option<RowCol> {
+        self.ledger.observe(&robot_map(world)?, self.day);
+        self.ledger.best(&target, self.position(), load, self.day, |c| self.memory.avoids(c, self.day))
+    }
+
+    // how much a market or bank next to the robot should be able to take for it to stop there
+    fn room_needed(&mut self, world: &World, target: Content, load: usize) -> usize {
+        if let Some(map) = robot_map(world) {
+            self.ledger.observe(&map, self.day);
+        }
+        self.ledger.room_needed(&target, load, self.day, |c| self.memory.avoids(c, self.day))
+    }
+
+    // heads to the market or bank that can take the load
+    fn set_room_destination(&mut self, world: &mut World, target: Content, load: usize) {
+        match self.find_room(world, target.clone(), load) {
+            | Some(c) if self.can_reach(world, c) => {
+                let left = self.ledger.left(c, self.day).map(|left| left.to_string()).unwrap_or_else(|| "unknown".to_string());
+                println!("{}", format!("Found a {target} at {c} with room for {left}, need {load}").color(Color::BrightGreen));
+                self.compass.set_destination(c.into());
+                self.set_objective(Moving(true), Reason::DestinationFound);
+                self.prepare_trip(world);
+            }
+            | _ => {
+                println!("{}", format!("No {target} with room for {load} in the map").color(Color::BrightRed));
+                self.set_objective(Exploring, Reason::NotFound);
+            }
+        }
+    }
+
+    // logs a visit to a place, along with what's there right now
+    fn record_visit(&mut self, world: &World, c: RowCol, outcome: Outcome) {
+        let found = robot_map(world)
+            .and_then(|map| map.get(c.row)?.get(c.col)?.as_ref().map(|tile| tile.content.clone()))
+            .unwrap_or(Content::None);
+        self.visits.record(c, self.day, found, outcome);
+    }
+
+    // the current destination can't be reached, don't go back there for a while
+    fn mark_unreachable(&mut self) {
+        if let Some(destination) = self.compass_destination() {
+            self.memory.remember(destination, Avoid::Unreachable, self.day);
+        }
+    }
+
+    // the robot got to its destination: if it was a spyglass hit, look around to check it was really there
+    fn check_spyglass_hit(&mut self, world: &mut World) {
+        let Some(destination) = self.compass_destination() else {
+            return;
+        };
+        if !self.spyglass.is_pending(destination) {
+            return;
+        }
+        let _ = robot_view(self, world);
+        let tile = robot_map(world).and_then(|map| map.get(destination.row)?.get(destination.col)?.clone());
+        if !self.spyglass.confirm(destination, tile.as_ref()) {
+            self.memory.remember(destination, Avoid::FalsePositive, self.day);
+        }
     }
 
-    // replaces the current objective with the next one
-    fn next_objective(&mut self) {
-        self.set_objective(self.next.clone());
-        self.next = Objective::None;
+    // state used by the tasks to check if they can still be carried out
+    fn task_state(&self) -> TaskState {
+        TaskState {
+            energy: self.get_energy().get_energy_level(),
+            backpack: self.get_backpack().get_contents(),
+        }
     }
 
     // collects the tent from the map
@@ -256,40 +516,36 @@ impl PioneerBot<'_> {
 
         // finds a suitable location to place the tent otherwise
         else {
-            match Spyglass::new(
-                self.get_coordinate().get_row(),
+            let can_sleep = |tile: &Tile| {
+                tile.content == Content::None
+                    && tile.tile_type.properties().can_hold(&Tent(0))
+                    && tile.tile_type.properties().walk()
+            };
+            let known = robot_map(world).unwrap_or_default();
+            let result = Spyglass::new(
                 self.get_coordinate().get_row(),
-                3,
+                self.get_coordinate().get_col(),
+                self.config.tent_spyglass_radius,
                 robot_map(world).unwrap().len(),
                 None,
                 false,
                 0.5,
-                |tile| {
-                    tile.content == Content::None
-                        && tile.tile_type.properties().can_hold(&Tent(0))
-                        && tile.tile_type.properties().walk()
-                },
+                can_sleep,
             )
-                .new_discover(self, world)
-            {
-                | SpyglassResult::Stopped(vec) => {
-
-                    // for some reason the spyglass will sometimes include tiles that do not match the criteria
-                    for (tile, row, col) in vec.iter() {
-                        if tile.content == Content::None
-                            && tile.tile_type.properties().walk()
-                            && tile.tile_type.properties().can_hold(&Tent(0)) {
-                            println!("{}", format!("Found a place to sleep at ({},{})", row, col).color(Color::BrightGreen));
-                            self.compass
-                                .set_destination(Destination::Coordinate((*row, *col)));
-                            self.set_next(Sleeping);
-                            self.set_objective(Moving(false));
-                            return Err(());
-                        }
-                    }
+                .new_discover(self, world);
+            let (hits, stats) = self.spyglass.verify(result, &known, self.position(), can_sleep);
+            println!("Spyglass: {stats} ({})", self.spyglass);
+
+            match hits.first() {
+                | Some(hit) => {
+                    println!("{}", format!("Found a place to sleep at {}", hit.1).color(Color::BrightGreen));
+                    self.spyglass.follow(hit, self.day);
+                    self.compass.set_destination(hit.1.into());
+                    self.set_next(Sleeping);
+                    self.set_objective(Moving(false), Reason::SleepSpotFound);
                     Err(())
                 }
-                | _ => {
+                | None => {
                     println!("I'll just sleep here for today");
                     Ok(())
                 }
@@ -297,104 +553,46 @@ impl PioneerBot<'_> {
         };
     }
 
-    // sets a destination in the zone that is recognized as least explored
-    fn set_random_destination(&mut self, world: &mut World) {
-        let mut map = robot_map(world).unwrap();
-        let mut dim = map.len();
-
-        // decide the precision (iterations of the loop) to apply to the search function
-        // based on the map length and chance. This way, calling it more than once consecutively is not
-        // guaranteed to yield the same result, and it might lead the robot to different areas even though they all
-        // fall under 'least explored'
-        let precision = random::<u32>() % dim.ilog2() + 1;
-        println!("precision: {precision}");
-
-        // setup to find the least explored
-        let mut target = (dim / 2, dim / 2);
-        let mut min = u32::MAX;
-        let mut min_quadrant = 0;
-
-        for _ in 1..=precision {
-            // setup 4 quadrants from the map
-            //       |
-            //    0  |  1
-            // ------|------
-            //    3  |  2
-            //       |
-            let mut quadrant = Vec::new();
-            for i in 0..=3 {
-                quadrant.push(Vec::new());
-                for row in 0..=dim / 2 {
-                    quadrant[i].push(Vec::new());
-                    for _col in 0..=dim / 2 {
-                        // initially empty
-                        quadrant[i][row].push(None);
-                    }
-                }
-            }
+    // heads for the frontier of the known map (a tile next to undiscovered ones) where the robot
+    // expects to find out the most for the energy it takes to get there
+    // frontiers the robot couldn't reach are remembered as unreachable, so they are skipped for a while
+    fn set_frontier_destination(&mut self, world: &mut World) {
+        let Some(map) = robot_map(world) else {
+            return;
+        };
+        let max_cost = MAX_ENERGY.saturating_sub(self.config.energy_reserve);
+        let frontier = frontier::best(&map, &self.teleports, &self.costs, self.position(), |c| self.is_avoided(c), max_cost);
 
-            // fill the quadrants by mapping each tile from the robot map to the correct quadrant
-            let (mut row, mut col) = (0usize, 0usize);
-            while row < dim {
-                while col < dim {
-                    if row < dim / 2 {
-                        if col < dim / 2 {
-                            quadrant[0][row][col] = map[row][col].clone();
-                        } else if col < dim {
-                            quadrant[1][row][col - (dim / 2)] = map[row][col].clone();
-                        }
-                    } else if row < dim {
-                        if col < dim / 2 {
-                            quadrant[2][row - (dim / 2)][col] = map[row][col].clone();
-                        } else if col < dim {
-                            quadrant[3][row - (dim / 2)][col - (dim / 2)] = map[row][col].clone();
-                        }
-                    }
-                    col += 1;
-                }
-                row += 1;
-                col = 0;
+        match frontier {
+            | Some(frontier) => {
+                println!(
+                    "Exploring the frontier at {}: {} undiscovered tiles around it, about {} energy away",
+                    frontier.cell, frontier.gain, frontier.cost
+                );
+                self.compass.set_destination(frontier.cell.into());
+                self.set_objective(Moving(false), Reason::Frontier);
+                self.prepare_trip(world);
             }
-
-            // obtain the least explored quadrant by summing each entry in each quadrant, mapped as follows:
-            // Some(Tile) => 1
-            // None => 0
-            for i in 0..=3 {
-                let mut sum = 0;
-                for row in quadrant[i].iter() {
-                    for tile in row.iter() {
-                        if tile.is_some() {
-                            sum += 1;
-                        }
-                    }
-                }
-                // and comparing the sums of the tiles of each quadrant to find the minimum
-                if sum < min {
-                    min = sum;
-                    min_quadrant = i;
-                }
+            | None => {
+                println!("{}", "No frontier left to explore".color(Color::BrightRed));
+                self.explored_at = Some(known_tiles(&map));
+                self.stop_exploring();
             }
-            println!("Quadrant {min_quadrant} has {min} undiscovered tiles");
-            // finally set as target coordinate the coordinate in the center of the quadrant
-            target.0 = match min_quadrant {
-                | 0 | 1 => target.0 - dim / 4,
-                | 3 | 2 => target.0 + dim / 4,
-                | _ => target.0,
-            };
-            target.1 = match min_quadrant {
-                | 0 | 3 => target.1 - dim / 4,
-                | 1 | 2 => target.1 + dim / 4,
-                | _ => target.1,
-            };
-
-            // set the map to the previous minimum quadrant, for the eventual next loop
-            dim /= 2;
-            map = quadrant[min_quadrant].clone();
         }
+    }
 
-        println!("Random destination set: {:?}", target);
-        self.compass.set_destination(Destination::Coordinate(target));
-        self.set_objective(Moving(false));
+    // there is nothing left to explore: drops the current task along with the one waiting for
+    // something to be found, so that the robot goes on with whatever comes next, or checks whether the game is over
+    fn stop_exploring(&mut self) {
+        self.compass.clear_destination();
+        self.tasks.pop();
+        match self.tasks.current() {
+            | Gathering(_) | Selling(_) | Depositing => {
+                println!("{}", format!("Giving up on {} as well", self.tasks.current()).color(Color::BrightRed));
+                self.tasks.pop();
+            }
+            | _ => {}
+        }
     }
 
     // tries to set the best destination given a target content and the next day's weather
@@ -409,30 +607,35 @@ impl PioneerBot<'_> {
         // if the weather is good, find the most loaded location
         // (assume it might be further away)
         if let Ok(WeatherType::Sunny) = next_weather {
-            if let Ok(c) = self.map.find_most_loaded(world, self, target_content.clone()) {
-                self.compass.set_destination(Destination::Coordinate(swap_coordinates(c.into())));
-                println!("Found the most {target_content} at {:?} in the map", swap_coordinates(c.into()));
-                destination_found = true;
+            if let Some(c) = self.find_most_loaded(world, target_content.clone()) {
+                if !self.is_avoided(c) && self.can_reach(world, c) {
+                    self.compass.set_destination(c.into());
+                    println!("Found the most {target_content} at {c} in the map");
+                    destination_found = true;
+                }
             }
         }
         // otherwise stick to the closest location, so that the bot doesn't go too far off the presumed safe spot it's in
-        else if let Ok(c) = self.map.find_closest(world, self, target_content.clone()) {
-            if !self.bankrupt.contains(&swap_coordinates(c.into())) {
-                self.compass.set_destination(Destination::Coordinate(swap_coordinates(c.into())));
-                println!("{}", format!("Found the closest {target_content} at {:?} in the map", swap_coordinates(c.into())).color(Color::BrightGreen));
-                destination_found = true;
-            } else if let Ok(c) = self.map.find_most_loaded(world, self, target_content.clone()) {
-                self.compass.set_destination(Destination::Coordinate(swap_coordinates(c.into())));
-                println!("Found the most {target_content} at {:?} in the map", swap_coordinates(c.into()));
+        else if let Some(c) = self.find_closest(world, target_content.clone()) {
+            if !self.is_avoided(c) && self.can_reach(world, c) {
+                self.compass.set_destination(c.into());
+                println!("{}", format!("Found the closest {target_content} at {c} in the map").color(Color::BrightGreen));
                 destination_found = true;
+            } else if let Some(c) = self.find_most_loaded(world, target_content.clone()) {
+                if !self.is_avoided(c) && self.can_reach(world, c) {
+                    self.compass.set_destination(c.into());
+                    println!("Found the most {target_content} at {c} in the map");
+                    destination_found = true;
+                }
             }
         }
 
         if !destination_found {
             println!("{}", format!("{target_content} not found in the map").color(Color::BrightRed));
-            self.set_objective(Exploring);
+            self.set_objective(Exploring, Reason::NotFound);
         } else {
-            self.set_objective(Moving(discover_new));
+            self.set_objective(Moving(discover_new), Reason::DestinationFound);
+            self.prepare_trip(world);
         }
     }
 
@@ -508,18 +711,80 @@ impl PioneerBot<'_> {
                 }
             }
         }
-        return None;
+
+        // nothing the robot can face from here, look further in the known map
+        if move_allowed {
+            return self.approach_target(world, &target);
+        }
+        None
+    }
+
+    // looks for the target within the configured radius in the known map, walks to the cheapest tile
+    // next to one and returns the direction to face it
+    fn approach_target<Target: Fn(&Tile) -> bool>(&mut self, world: &mut World, target: &Target) -> Option<Direction> {
+        let map = robot_map(world)?;
+        let position = self.position();
+        let radius = self.config.face_target_radius;
+        let walkable = |c: RowCol| {
+            c == position || map[c.row][c.col].as_ref().map(|tile| self.costs.step(None, tile).is_some()).unwrap_or(false)
+        };
+        // teleports would take the robot away from the target
+        let no_teleports = BTreeSet::new();
+
+        // directions to the tile to stand on, their cost and the direction to face from there
+        let mut best: Option<(Vec<Direction>, usize, Direction)> = None;
+        for row in position.row.saturating_sub(radius)..=(position.row + radius).min(map.len().saturating_sub(1)) {
+            for col in position.col.saturating_sub(radius)..=(position.col + radius).min(map[row].len().saturating_sub(1)) {
+                if !map[row][col].as_ref().map(|tile| target(tile)).unwrap_or(false) {
+                    continue;
+                }
+                let sides = [
+                    (row.checked_sub(1).map(|r| RowCol::new(r, col)), Down),
+                    (Some(RowCol::new(row + 1, col)).filter(|c| c.row < map.len()), Up),
+                    (col.checked_sub(1).map(|c| RowCol::new(row, c)), Right),
+                    (Some(RowCol::new(row, col + 1)).filter(|c| c.col < map[row].len()), Left),
+                ];
+                for (stand, facing) in sides {
+                    let Some(stand) = stand.filter(|c| walkable(*c)) else {
+                        continue;
+                    };
+                    let Some(route) = pathfinder::route(&map, &no_teleports, &self.costs, position, stand) else {
+                        continue;
+                    };
+                    if best.as_ref().map(|(_, cost, _)| route.cost < *cost).unwrap_or(true) {
+                        let directions = route
+                            .steps
+                            .into_iter()
+                            .filter_map(|step| match step {
+                                | Step::Go(direction) => Some(direction),
+                                | Step::Teleport(_) => None,
+                            })
+                            .collect();
+                        best = Some((directions, route.cost, facing));
+                    }
+                }
+            }
+        }
+
+        let (directions, _, facing) = best?;
+        println!("Walking {} steps to face the target", directions.len());
+        for direction in directions {
+            if go(self, world, direction).is_err() {
+                return None;
+            }
+        }
+        Some(facing)
     }
 
     // returns the coordinates of the next tile in the direction provided
-    fn look_ahead(&self, world: &World, direction: Direction) -> Option<(usize, usize)> {
+    fn look_ahead(&self, world: &World, direction: Direction) -> Option<RowCol> {
         let (row, col) = self.get_coordinate_usize();
         let dim = robot_map(world).unwrap().len();
         match direction {
-            | Up => if row > 0 { Some((row - 1, col)) } else { None },
-            | Down => if row < dim - 1 { Some((row + 1, col)) } else { None },
-            | Left => if col > 0 { Some((row, col - 1)) } else { None },
-            | Right => if col < dim - 1 { Some((row, col + 1)) } else { None },
+            | Up => if row > 0 { Some(RowCol::new(row - 1, col)) } else { None },
+            | Down => if row < dim - 1 { Some(RowCol::new(row + 1, col)) } else { None },
+            | Left => if col > 0 { Some(RowCol::new(row, col - 1)) } else { None },
+            | Right => if col < dim - 1 { Some(RowCol::new(row, col + 1)) } else { None },
         }
     }
 
@@ -534,13 +799,12 @@ impl PioneerBot<'_> {
             println!("{}", "Following my heart and not my compass".color(Color::BrightRed));
         }
 
-        if let Some(Destination::Coordinate((dest_row, dest_col))) = *self.compass.get_destination() {
+        if let Some(RowCol { row: dest_row, col: dest_col }) = self.compass_destination() {
 
-            // try to take between 2 and 8 steps  going blindly towards the destination
+            // try to take up to 8 steps going blindly towards the destination
             // (it will stop if it's stuck)
-            let steps = random::<u8>() % 4 + 1;
             let (mut stuck_row, mut stuck_col) = (false, false);
-            for _ in 1..=steps {
+            for _ in 0..BLIND_ROUNDS {
                 let (curr_row, curr_col) = self.get_coordinate_usize();
                 if let Err(MoveError::AlreadyAtDestination) = self.compass.get_move(&robot_map(world).unwrap(), self.get_coordinate_usize()) {
                     break;
@@ -581,14 +845,13 @@ impl PioneerBot<'_> {
             // refresh the destination
             if self.compass.get_destination().is_some() {
                 self.compass.clear_destination();
-                self.compass
-                    .set_destination(Destination::Coordinate((dest_row, dest_col)));
+                self.compass.set_destination(RowCol::new(dest_row, dest_col).into());
             }
         }
         // it should never get in here as I always use Destination::Coordinate when moving,
-        // but just in case I am wrong set a random destination
+        // but just in case I am wrong go explore
         else {
-            self.set_random_destination(world);
+            self.set_frontier_destination(world);
         }
 
         // remove the last two coordinates from the list, then add back the current ones
@@ -599,55 +862,371 @@ impl PioneerBot<'_> {
         self.last_coords.push(self.get_coordinate_usize());
     }
 
+    fn compass_destination(&self) -> Option<RowCol> {
+        match self.compass.get_destination() {
+            | Some(Destination::Coordinate((row, col))) => Some(RowCol::new(*row, *col)),
+            | _ => None,
+        }
+    }
+
+    // whether there are steps left in a route of the pathfinder to the current destination
+    fn following_route(&self) -> bool {
+        let destination = self.compass_destination();
+        matches!(&self.route, Some((target, steps)) if !steps.is_empty() && Some(*target) == destination)
+    }
+
+    // next step of the route found by the pathfinder, as long as it still leads to the current destination
+    fn next_route_step(&mut self) -> Option<Step> {
+        let destination = self.compass_destination();
+        match self.route.as_mut() {
+            | Some((target, steps)) if Some(*target) == destination => steps.pop(),
+            | _ => {
+                self.route = None;
+                None
+            }
+        }
+    }
+
+    // lets the pathfinder take over from the compass, returns whether it found a route
+    fn reroute(&mut self, world: &World) -> bool {
+        self.remember_teleports(world);
+        let route = self.compass_destination().and_then(|destination| {
+            pathfinder::route(&robot_map(world)?, &self.teleports, &self.costs, self.position(), destination)
+                .map(|route| (destination, route))
+        });
+        match route {
+            | Some((destination, mut route)) => {
+                println!("{}", format!("Compass is lost, following a route of {} steps", route.steps.len()).color(Color::BrightYellow));
+                route.steps.reverse();
+                self.route = Some((destination, route.steps));
+                true
+            }
+            | None => false,
+        }
+    }
+
+    // tile the robot is standing on, as it appears in the known map
+    fn tile_here(&self, world: &World) -> Option<Tile> {
+        let position = self.position();
+        robot_map(world)?.get(position.row)?.get(position.col)?.clone()
+    }
+
+    // teleports can only be used once the robot has stepped on them,
+    // which is when they show up as active in the known map
+    fn remember_teleports(&mut self, world: &World) {
+        let Some(map) = robot_map(world) else {
+            return;
+        };
+        for (row, tiles) in map.iter().enumerate() {
+            for (col, tile) in tiles.iter().enumerate() {
+                let c = RowCol::new(row, col);
+                if let Some(Tile { tile_type: TileType::Teleport(true), .. }) = tile {
+                    // teleports that didn't work are left out until the robot stops avoiding them
+                    if !self.memory.avoids(c, self.day) && self.teleports.insert(c) {
+                        println!("{}", format!("Found a teleport at {c}").color(Color::BrightCyan));
+                    }
+                }
+            }
+        }
+    }
+
+    // the compass only knows how to walk: if the cheapest known route to the destination
+    // goes through a teleport, follow that one instead
+    fn take_shortcut(&mut self, world: &World) {
+        let Some(destination) = self.compass_destination() else {
+            return;
+        };
+        if self.teleports.len() < 2 {
+            return;
+        }
+        let route = robot_map(world).and_then(|map| pathfinder::route(&map, &self.teleports, &self.costs, self.position(), destination));
+        if let Some(mut route) = route {
+            if route.steps.iter().any(|step| matches!(step, Step::Teleport(_))) {
+                println!("{}", format!("Taking a shortcut through a teleport, about {} energy", route.cost).color(Color::BrightCyan));
+                route.steps.reverse();
+                self.route = Some((destination, route.steps));
+            }
+        }
+    }
+
+    // estimated energy needed to get to the destination, based on the tiles known so far
+    fn trip_cost(&self, world: &World, destination: RowCol) -> Option<usize> {
+        pathfinder::route(&robot_map(world)?, &self.teleports, &self.costs, self.position(), destination).map(|route| route.cost)
+    }
+
+    // whether the robot could get to the destination on a full charge, keeping the energy reserve
+    fn can_reach(&self, world: &World, destination: RowCol) -> bool {
+        match self.trip_cost(world, destination) {
+            | Some(cost) if cost + self.config.energy_reserve > MAX_ENERGY => {
+                println!("{}", format!("{destination} is too far, it would take about {cost} energy").color(Color::BrightRed));
+                false
+            }
+            | _ => true,
+        }
+    }
+
+    // called right after setting off to a destination
+    fn prepare_trip(&mut self, world: &World) {
+        self.remember_teleports(world);
+        self.take_shortcut(world);
+        self.charge_for_trip(world);
+    }
+
+    // if the robot doesn't have enough energy to get to the destination and keep the reserve, it charges first
+    fn charge_for_trip(&mut self, world: &World) {
+        let cost = self.compass_destination().and_then(|destination| self.trip_cost(world, destination));
+        if let Some(cost) = cost {
+            let energy = self.get_energy().get_energy_level();
+            let needed = (cost + self.config.energy_reserve).min(MAX_ENERGY);
+            if needed > energy {
+                println!("{}", format!("Getting there takes about {cost} energy, but I only have {energy}: charging first").color(Color::Cyan));
+                self.interrupt(Charging(needed), Reason::LowEnergy);
+            } else {
+                println!("Getting there takes about {cost} energy, I have {energy}");
+            }
+        }
+    }
+
     // returns the best content to sell at the moment, based on quantity
-    // owned and price
-    fn get_content_to_sell(&self) -> Content {
-        let (mut max_content, mut max_cost) = (Content::None, 0usize);
+    // owned and the prices learned at the closest market
+    fn get_content_to_sell(&self, world: &mut World) -> Content {
+        let market = self.find_closest(world, Content::Market(0));
+        content_to_sell(self.get_backpack().get_contents(), |content| match market {
+            | Some(market) => self.prices.price_at(content, market),
+            | None => self.prices.price(content),
+        })
+    }
+
+    // distance from the robot to the closest known location of the contents a strategy might be interested in
+    fn known_distances(&mut self, world: &mut World) -> HashMap<Content, usize> {
+        let position = self.position();
+        let mut distances = HashMap::new();
+        for content in [
+            Content::Rock(0),
+            Content::Tree(0),
+            Content::Fish(0),
+            Content::Market(0),
+            Content::Bank(0..0),
+            Content::Building,
+        ] {
+            if let Some(c) = self.find_closest(world, content.clone()) {
+                distances.insert(content, position.distance(c));
+            }
+        }
+        distances
+    }
+
+    // checks whether the objective chosen by the pilot makes sense in the current state
+    fn validate(&mut self, world: &mut World, objective: &Objective) -> Result<(), Rejection> {
+        let backpack = self.robot.backpack.get_contents();
+        let quantity = |content: &Content| *backpack.get(content).unwrap_or(&0);
+        let total = backpack.values().sum::<usize>();
+
+        match objective {
+            | Selling(content) => {
+                if quantity(content) == 0 {
+                    return Err(match content {
+                        | Content::Fish(_) => Rejection::NoFish,
+                        | Content::Tree(_) => Rejection::NoWood,
+                        | _ => Rejection::NoRocks,
+                    });
+                }
+                if self.find_closest(world, Content::Market(0)).is_none() {
+                    return Err(Rejection::NoMarket);
+                }
+            }
+            | Depositing => {
+                if quantity(&Content::Coin(0)) == 0 {
+                    return Err(Rejection::NoCoins);
+                }
+                if self.find_closest(world, Content::Bank(0..0)).is_none() {
+                    return Err(Rejection::NoBank);
+                }
+            }
+            | Gathering(_) => {
+                if total >= self.robot.backpack.get_size() {
+                    return Err(Rejection::BackpackFull);
+                }
+            }
+            | Charging(target_level) => {
+                if self.robot.energy.get_energy_level() >= *target_level {
+                    return Err(Rejection::EnergyFull);
+                }
+            }
+            | _ => {}
+        }
+        Ok(())
+    }
+
+    // plans a single trip through the known markets to sell everything sellable in the backpack
+    // (starting from the content that was decided), then to a bank to deposit the coins
+    // returns false if there is no such trip, in which case the robot goes to one market at a time
+    fn plan_tour(&mut self, world: &mut World, content: Content) -> bool {
+        let Some(map) = robot_map(world) else {
+            return false;
+        };
+        self.ledger.observe(&map, self.day);
+        let position = self.position();
+        let backpack = self.get_backpack().get_contents();
+        let mut sell = vec![content.clone()];
+        sell.extend(
+            SELLABLE
+                .iter()
+                .filter(|c| **c != content && *backpack.get(c).unwrap_or(&0) > 0)
+                .cloned(),
+        );
+
+        // only the markets and banks with room for the load are worth a stop: each market is asked to take
+        // one content at a time, and the bank at least the coins the robot already has
+        let sell_load = sell.iter().map(|c| *backpack.get(c).unwrap_or(&0)).max().unwrap_or(0);
+        let coins = *backpack.get(&Content::Coin(0)).unwrap_or(&0);
+        let avoided = |c| self.memory.avoids(c, self.day);
+        let reachable = |c: &RowCol| self.can_reach(world, *c);
+        let markets =
+            self.ledger.with_room(&Content::Market(0), position, sell_load, self.day, tour::CANDIDATES, avoided);
+        let markets = markets.into_iter().filter(reachable).collect::<Vec<_>>();
+        let banks = self.ledger.with_room(&Content::Bank(0..0), position, coins, self.day, tour::CANDIDATES, avoided);
+        let banks = banks.into_iter().filter(reachable).collect::<Vec<_>>();
+
+        let Some((stops, cost)) = TourPlanner::new(&map, &self.teleports, &self.costs).plan(position, &sell, &markets, &banks) else {
+            return false;
+        };
+        println!("{}", format!("Planned a tour of {} stops, about {cost} energy:", stops.len()).color(Color::BrightGreen));
+        self.refresh_towns(world);
+        for stop in stops.iter() {
+            match self.settlements.town_at(stop.location) {
+                | Some(town) => println!("  {} at {} in {}", stop.objective, stop.location, town.name),
+                | None => println!("  {} at {}", stop.objective, stop.location),
+            }
+        }
+
+        // the stops are queued right after the current objective, so the last one goes first
+        for i in (1..stops.len()).rev() {
+            self.set_next(stops[i].objective.clone());
+            if stops[i].location != stops[i - 1].location {
+                self.set_next_trip(stops[i].location, stops[i - 1].location.distance(stops[i].location));
+            }
+        }
+        self.set_next(stops[0].objective.clone());
+        self.compass.set_destination(stops[0].location.into());
+        self.set_objective(Moving(true), Reason::DestinationFound);
+        self.prepare_trip(world);
+        true
+    }
 
-        // costs of the various items at the shop
-        let mut costs = HashMap::new();
-        costs.insert(Content::Rock(0), 1);
-        costs.insert(Content::Tree(0), 2);
-        costs.insert(Content::Fish(0), 3);
-        costs.insert(Content::Coin(0), 3);
+    // looks for new towns in the known map, and lists the known ones if anything changed
+    fn refresh_towns(&mut self, world: &World) {
+        let Some(map) = robot_map(world) else {
+            return;
+        };
+        if self.settlements.update(&map) {
+            println!("{}", "Known towns:".color(Color::BrightWhite));
+            for town in self.settlements.towns() {
+                println!("  {town}");
+            }
+        }
+    }
 
-        for (content, quantity) in self.get_backpack().get_contents().iter() {
-            if let Content::Tree(_) | Content::Rock(_) | Content::Fish(_) = content {
-                let cost = *quantity * *costs.get(&content).unwrap();
-                if cost > max_cost {
-                    max_cost = cost;
-                    max_content = content.clone();
+    // carries out the objective decided by the strategy in the Praying phase,
+    // setting up the destination it needs first
+    fn pursue(&mut self, world: &mut World, objective: Objective) {
+        let next_weather = self.forecast.predict_from_time(0, 24);
+        match objective {
+            // reach a shelter and wait there
+            | Waiting(target_time) => {
+                // any building, market or bank will do, so head for the closest town
+                self.refresh_towns(world);
+                let position = self.position();
+                let shelter = self.settlements.closest(position, None).and_then(|town| {
+                    println!("found {}", town.name);
+                    town.closest(position, None)
+                });
+                if let Some(c) = shelter {
+                    self.compass.set_destination(c.into());
+                    self.set_objective(Moving(true), Reason::Shelter);
+                } else if let Some(c) = self.find_closest(world, Content::Tree(0)) {
+                    println!("found a tree");
+                    self.compass.set_destination(c.into());
+                    self.set_objective(Moving(false), Reason::Shelter);
+                } else {
+                    println!("found none! to exploring then");
+                    self.set_objective(Exploring, Reason::NotFound)
+                }
+
+                // wait till the night at the shelter
+                self.set_next(Waiting(target_time));
+            }
+            | Selling(content) => {
+                if !self.plan_tour(world, content.clone()) {
+                    let load = *self.get_backpack().get_contents().get(&content).unwrap_or(&0);
+                    self.set_next(Selling(content));
+                    self.set_room_destination(world, Content::Market(0), load);
                 }
             }
+            | Depositing => {
+                let load = *self.get_backpack().get_contents().get(&Content::Coin(0)).unwrap_or(&0);
+                self.set_next(Depositing);
+                self.set_room_destination(world, Content::Bank(0..0), load);
+            }
+            | Gathering(content) => {
+                self.set_next(Gathering(content.clone()));
+                self.set_best_destination(world, content, next_weather, false);
+            }
+            | objective => self.set_objective(objective, Reason::Decided),
         }
-        max_content
     }
 
     // main function, called each tick
     fn auto_pilot(&mut self, world: &mut World, assisted: bool) {
         if let DayTime::Night = look_at_sky(world).get_time_of_day() {
-            if let Sleeping | Waiting(_) = self.objective {
+            if let Sleeping | Waiting(_) = self.tasks.current() {
                 // the robot is already sleeping
-            } else if let Sleeping = self.next {
+            } else if let Sleeping = self.tasks.next() {
                 // the robot is moving towards a place to put the tent
             } else {
+                // whatever the robot was doing, it will be resumed in the morning
                 println!("{}", "-> time to sleep!".color(Color::BrightMagenta));
-                self.set_objective(Sleeping);
+                self.interrupt(Sleeping, Reason::Nightfall);
             }
         }
 
         // check if energy level critical
-        else if self.get_energy().get_energy_level() < 150 {
-            match (&self.objective, &self.next) {
-                | (&Waiting(_), _) | (&Charging(_), _) | (&Sleeping, _) | (_, &Sleeping) => {}
-                | _ => {
-                    self.set_next(self.objective.clone());
-                    self.set_objective(Charging(250));
-                }
+        else if self.get_energy().get_energy_level() < self.config.low_energy {
+            match (self.tasks.current(), self.tasks.next()) {
+                | (Waiting(_), _) | (Charging(_), _) | (Sleeping, _) | (_, Sleeping) => {}
+                | _ => self.interrupt(Charging(self.config.recharge_to), Reason::LowEnergy),
+            }
+        }
+
+        // keep track of where the robot goes while moving, to tell when it's stuck
+        if let Moving(_) = self.tasks.current() {
+            self.stuck.record(self.position(), self.compass_destination());
+        }
+
+        // give up on the current task if it's taking too long, or if the robot
+        // is going around in circles even when following the route of the pathfinder
+        // (a route can lead away from the destination for a while, so that doesn't count)
+        if self.tasks.top().map(|task| task.over_budget()).unwrap_or(false) {
+            println!("It's taking too long");
+            self.abandon_objective(Reason::OverBudget);
+        } else if let (Moving(_), true) = (self.tasks.current(), self.following_route()) {
+            if let Some(stuck @ (Stuck::PingPong | Stuck::Cycle(_))) = self.stuck.check() {
+                println!("{stuck}");
+                self.abandon_objective(Reason::Stuck);
             }
         }
 
-        match self.objective.clone() {
+        // drop the tasks that are already done, or that can't be carried out anymore
+        while let Some(task) = self.tasks.top() {
+            let state = self.task_state();
+            if !task.is_complete(&state) && task.precondition(&state) {
+                break;
+            }
+            println!("Nothing left to do for {}", task.objective);
+            self.next_objective(Reason::TaskDone);
+        }
+
+        match self.tasks.current() {
             // the robot is deciding what to do next
             // either on autopilot or by user choice
             | Praying => {
@@ -665,7 +1244,7 @@ impl PioneerBot<'_> {
 
                     // get the objective chosen by the pilot
                     if let Some(pilot) = self.pilot.as_mut() {
-                        std::thread::sleep(std::time::Duration::from_millis(500));
+                        std::thread::sleep(std::time::Duration::from_millis(self.config.pilot_delay_ms));
                         println!("{}", "Decide what to do now:".color(Color::BrightWhite));
                         pilot_objective = match pilot.get_objective() {
                             | Ok(o) => o,
@@ -677,115 +1256,57 @@ impl PioneerBot<'_> {
                     }
                 }
 
+                // make sure the choice of the pilot can be carried out,
+                // otherwise tell them why and let the AI decide instead
+                if assisted && pilot_objective != Objective::None {
+                    if let Err(rejection) = self.validate(world, &pilot_objective) {
+                        println!("{}", format!("Can't do {pilot_objective}: {rejection}").color(Color::BrightRed));
+                        if let Some(pilot) = self.pilot.as_mut() {
+                            pilot.put_rejection(rejection);
+                        }
+                        pilot_objective = Objective::None;
+                    }
+                }
+
                 if assisted && pilot_objective != Objective::None {
                     // do what the pilot decided
-                    self.set_next(Objective::None);
-                    self.set_objective(pilot_objective);
+                    self.set_objective(pilot_objective, Reason::PilotChoice);
                 }
 
                 // if there is no pilot, or they decided not to intervene,
                 // go on autopilot and let the AI decide what to do
                 else {
-                    let weather = look_at_sky(world).get_weather_condition();
-                    let next_weather = self.forecast.predict_from_time(0, 24);
-
-                    // if current weather is bad, sleep for the day
-                    if let WeatherType::TrentinoSnow | WeatherType::TropicalMonsoon = weather {
-                        println!("The weather today is {weather:?}");
-                        self.set_objective(Sleeping);
-                    }
-
-                    // if the weather for the next day is bad, move close to a town
-                    else if let Ok(WeatherType::TrentinoSnow | WeatherType::TropicalMonsoon) = next_weather.as_ref() {
-                        println!("The weather tomorrow is {:?}", next_weather.as_ref().unwrap());
-                        print!("Decided to reach shelter from tomorrow's storm and ");
-                        if let Ok(c) = self.map.find_closest(world, self, Content::Building) {
-                            println!("found some buildings");
-                            self.set_objective(Moving(true));
-                            self.compass.set_destination(Destination::Coordinate(swap_coordinates(c.into())));
-                        } else if let Ok(c) = self.map.find_closest(world, self, Content::Market(0)) {
-                            println!("found a market");
-                            self.set_objective(Moving(true));
-                            self.compass.set_destination(Destination::Coordinate(swap_coordinates(c.into())));
-                        } else if let Ok(c) = self.map.find_closest(world, self, Content::Bank(0..0)) {
-                            println!("found a bank");
-                            self.set_objective(Moving(true));
-                            self.compass.set_destination(Destination::Coordinate(swap_coordinates(c.into())));
-                        } else if let Ok(c) = self.map.find_closest(world, self, Content::Tree(0)) {
-                            println!("found a tree");
-                            self.compass.set_destination(Destination::Coordinate(swap_coordinates(c.into())));
-                            self.set_objective(Moving(false));
-                        } else {
-                            println!("found none! to exploring then");
-                            self.set_objective(Exploring)
-                        }
-
-                        // wait till the night at the shelter
-                        self.set_next(Waiting(DayTime::Night));
-                    }
-
-                    // if the backpack is more than 80% full, go to the market and sell
-                    else if self.get_backpack().get_contents().values().sum::<usize>()
-                        >= self.get_backpack().get_size() * 4 / 5 {
-
-                        // select the item that would make the most money in the current held quantity
-                        let sellable_content = self.get_content_to_sell();
-
-                        // check if it-s better to deposit coins in the bank before
-                        let backpack = self.get_backpack().get_contents();
-                        let target_content = if *backpack.get(&Content::Coin(0)).unwrap_or(&0)
-                            > *backpack.get(&sellable_content).unwrap_or(&0)
-                        {
-                            println!("Decided to deposit my coins");
-                            self.set_next(Depositing);
-                            Content::Bank(0..0)
-                        } else {
-                            println!("Decided to sell some {sellable_content}");
-                            self.set_next(Selling(sellable_content.clone()));
-                            Content::Market(0)
-                        };
-                        self.set_best_destination(world, target_content.clone(), next_weather, true);
-                    }
+                    let known_map = robot_map(world).unwrap();
+                    let forecast = self.forecast.predict_from_time(0, 24).ok();
+                    let distances = self.known_distances(world);
+                    let snapshot = Snapshot {
+                        backpack: self.robot.backpack.get_contents(),
+                        backpack_size: self.robot.backpack.get_size(),
+                        energy: self.robot.energy.get_energy_level(),
+                        weather: look_at_sky(world).get_weather_condition(),
+                        forecast,
+                        known_map: &known_map,
+                        distances,
+                        step_energy: self.costs.average_step(),
+                        prices: self.prices.prices(),
+                        config: &self.config,
+                    };
+                    let mut plan = self.strategy.plan(&snapshot, &mut self.rng);
 
-                    // if the backpack is less than 60% full, gather some content
-                    else if self.get_backpack().get_contents().values().sum::<usize>()
-                        <= self.get_backpack().get_size() * 3 / 5 {
-
-                        // select the item of which the robot holds most
-                        let (mut max_content, mut max_quantity) = (Vec::new(), 0);
-                        for (content, quantity) in self.get_backpack().get_contents().iter() {
-                            if let Content::Tree(_) | Content::Rock(_) | Content::Fish(_) = content {
-                                if *quantity > max_quantity {
-                                    max_quantity = *quantity;
-                                    max_content.clear();
-                                    max_content.push(content.clone());
-                                } else if *quantity == max_quantity {
-                                    max_content.push(content.clone());
-                                }
-                            }
+                    // queue the rest of the plan after the first step
+                    if !plan.is_empty() {
+                        let objective = plan.remove(0);
+                        for next in plan.into_iter().rev() {
+                            self.set_next(next);
                         }
-
-                        // choose randomly if more than one have the same quantity
-                        let range = max_content.len();
-                        let target_content = max_content[random::<usize>() % range].clone();
-
-
-                        println!("Decided to gather some {target_content}");
-                        self.set_next(Gathering(target_content.clone()));
-                        self.set_best_destination(world, target_content.clone(), next_weather, false);
-                    }
-
-                    // if there is nothing else to do, explore
-                    else {
-                        println!("Decided to explore");
-                        self.set_objective(Exploring);
+                        self.pursue(world, objective);
                     }
                 }
             }
 
             | Waiting(target_time) => {
                 println!(".");
-                std::thread::sleep(std::time::Duration::from_millis(100));
+                std::thread::sleep(std::time::Duration::from_millis(self.config.waiting_delay_ms));
 
                 let current_time = look_at_sky(world).get_time_of_day();
                 if current_time == target_time {
@@ -798,7 +1319,7 @@ impl PioneerBot<'_> {
                     if *self.get_backpack().get_contents().get(&Tent(0)).unwrap() == 0usize {
                         self.retrieve_tent(world);
                     }
-                    self.next_objective();
+                    self.next_objective(Reason::WaitOver);
                 }
             }
 
@@ -807,7 +1328,7 @@ impl PioneerBot<'_> {
             | Moving(discover_new) => {
                 let map = robot_map(world);
 
-                // 25% chance to pick up random content while moving around
+                // chance to pick up random content while moving around
                 if let Some(direction) = self.face_target(world, false, |tile| {
                     if let Content::Rock(_) | Content::Tree(_) | Content::Fish(_) | Content::Coin(_) = tile.content {
                         true
@@ -815,8 +1336,9 @@ impl PioneerBot<'_> {
                         false
                     }
                 }) {
+                    let pick_up = self.rng.gen::<f32>() < self.config.pickup_chance;
                     let backpack = self.get_backpack();
-                    if random::<usize>() % 4 == 0
+                    if pick_up
                         && backpack.get_contents().values().sum::<usize>() < backpack.get_size()
                     {
                         if let Ok(_) = destroy(self, world, direction) {
@@ -825,12 +1347,12 @@ impl PioneerBot<'_> {
                     }
                 }
 
-                // 10% chance to use the spyglass with a reduced range, also to help with NLA pathfinding
-                if random::<u8>() % 10 == 0 {
+                // chance to use the spyglass with a reduced range, also to help with NLA pathfinding
+                if self.rng.gen::<f32>() < self.config.spyglass_chance {
                     let _ = Spyglass::new(
                         self.get_coordinate().get_row(),
                         self.get_coordinate().get_col(),
-                        5,
+                        self.config.moving_spyglass_radius,
                         robot_map(world).unwrap().len(),
                         Some(self.get_energy().get_energy_level() / 5),
                         false,
@@ -840,30 +1362,59 @@ impl PioneerBot<'_> {
                         .new_discover(self, world);
                 }
 
-                // need to constantly take random turns due to a bug in NLA compass,
-                // sometimes the robot just goes back and forth between two adjacent tiles
-                // this is not a fix, it still does that sometimes, but it seems to give
-                // the compass the opportunity to fix itself in some occasions
-                match self
-                    .compass
-                    .get_move(&map.clone().unwrap(), self.get_coordinate_usize())
-                {
-                    | Ok(direction) => {
-                        // if the robot keeps going back on its steps, leave a chance to
-                        // intervene and manually move it towards the destination, as it might mean it's stuck
-                        if let Some(next) = self.look_ahead(world, direction.clone()) {
-                            if self.last_coords.contains(&next) && random::<u8>() % 2 == 0
-                            {
-                                self.move_blindly(world);
+                // due to a bug in NLA compass, sometimes the robot just goes back and forth
+                // between two adjacent tiles, or around in circles: when that happens let the pathfinder
+                // take over, and if even that fails move blindly towards the destination
+                if let (false, Some(stuck)) = (self.following_route(), self.stuck.check()) {
+                    println!("{}", format!("Stuck: {stuck}").color(Color::BrightRed));
+                    self.stuck.clear();
+                    if !self.reroute(world) {
+                        self.move_blindly(world);
+                    }
+                }
+
+                // follow the route of the pathfinder if there is one, otherwise ask the compass
+                let step = match self.next_route_step() {
+                    | Some(step) => Ok(step),
+                    | None => match self.compass.get_move(&map.clone().unwrap(), self.get_coordinate_usize()) {
+                        | Ok(direction) => Ok(Step::Go(direction)),
+                        // the compass can't find a way, the pathfinder might
+                        | Err(e @ (MoveError::NoAvailableMove | MoveError::InvalidCurrPosition)) => {
+                            match self.reroute(world) {
+                                | true => self.next_route_step().ok_or(e),
+                                | false => Err(e),
                             }
                         }
-
-                        if let Err(LibError::CannotWalk) = go(self, world, direction.clone()) {
+                        | Err(e) => Err(e),
+                    },
+                };
+
+                match step {
+                    | Ok(Step::Teleport(c)) => {
+                        println!("{}", format!("Teleporting to {c}").color(Color::BrightCyan));
+                        if teleport(self, world, c.into()).is_err() {
+                            // the teleport might not be usable after all, find another way there
+                            println!("Can't teleport to {c} from here");
+                            self.teleports.remove(&c);
+                            self.memory.remember(c, Avoid::Unreachable, self.day);
+                            self.route = None;
+                        }
+                    }
+                    | Ok(Step::Go(direction)) => {
+                        let from = self.tile_here(world);
+                        self.step_energy = 0;
+                        let result = go(self, world, direction.clone());
+                        if let (Ok(_), Some(from), Some(to)) = (&result, from, self.tile_here(world)) {
+                            self.costs.learn(&from, &to, self.step_energy);
+                        }
+                        if let Err(LibError::CannotWalk) = result {
                             println!("Can't go {direction:?} from here");
+                            // the known map was wrong, the route needs to be found again
+                            self.route = None;
 
                             // if the robot is moving towards a content
-                            if let Gathering(content) = self.next.clone() {
-                                println!("Next I have to be {:?}", self.next);
+                            if let Gathering(content) = self.tasks.next() {
+                                println!("Next I have to be {:?}", self.tasks.next());
 
                                 // checks if the content is actually right in front of it
                                 // (mostly happens in case of fish)
@@ -871,13 +1422,12 @@ impl PioneerBot<'_> {
                                     .face_target(world, true, |tile| tile.content.to_default() == content.to_default())
                                 {
                                     println!("{content} is reachable {direction:?} from here");
+                                    if let Some(c) = self.look_ahead(world, direction.clone()) {
+                                        self.record_visit(world, c, Outcome::Gathered);
+                                    }
                                     let _ = destroy(self, world, direction);
                                     self.compass.clear_destination();
-
-                                    if discover_new {
-                                        self.pins.insert(self.get_coordinate_usize());
-                                    }
-                                    self.next_objective();
+                                    self.next_objective(Reason::DestinationReached);
                                 } else {
                                     // otherwise it needs to build a bridge to it
                                     print!("Building a road to the {content}..");
@@ -891,8 +1441,11 @@ impl PioneerBot<'_> {
                                             | Err(LibError::MustDestroyContentFirst) => {
                                                 if let Err(_) = destroy(self, world, direction.clone()) {
                                                     println!("can't reach that {content} right now..");
+                                                    self.mark_unreachable();
                                                     self.compass.clear_destination();
-                                                    self.set_objective(Praying);
+                                                    // give up on the gathering as well
+                                                    self.tasks.pop();
+                                                    self.set_objective(Praying, Reason::Unreachable);
                                                     break;
                                                 }
                                             }
@@ -906,7 +1459,7 @@ impl PioneerBot<'_> {
                                                     println!("\nI don't have enough rocks right now..");
                                                     self.compass.clear_destination();
                                                     self.set_next(Gathering(Content::Rock(0)));
-                                                    self.set_objective(Exploring);
+                                                    self.set_objective(Exploring, Reason::NotEnoughRocks);
                                                     break;
                                                 } else {
                                                     i += 1;
@@ -919,7 +1472,7 @@ impl PioneerBot<'_> {
                                                     "\nI'm too low on energy ({}/1000)",
                                                     self.get_energy().get_energy_level()
                                                 );
-                                                self.set_objective(Charging(300));
+                                                self.interrupt(Charging(self.config.bridge_recharge_to), Reason::LowEnergy);
                                                 break;
                                             }
 
@@ -929,9 +1482,10 @@ impl PioneerBot<'_> {
                                             }
                                             | _ => {
                                                 println!("can't reach that {content} right now..");
+                                                self.mark_unreachable();
                                                 self.compass.clear_destination();
-                                                self.set_next(Objective::None);
-                                                self.set_objective(Praying);
+                                                self.tasks.pop();
+                                                self.set_objective(Praying, Reason::Unreachable);
                                                 break;
                                             }
                                         }
@@ -947,7 +1501,8 @@ impl PioneerBot<'_> {
                                     if let (Some(audio), Some(sounds)) = (self.audio.as_mut(), self.sounds.as_ref()) {
                                         let _ = audio.play_audio(&sounds[1]);
                                     } else { println!("{}", "I hit an obstacle, backtracking".color(Color::BrightRed)); }
-                                    self.compass.set_destination(Destination::Coordinate(*coordinate));
+                                    let (row, col) = *coordinate;
+                                    self.compass.set_destination(RowCol::new(row, col).into());
                                     self.last_coords.clear();
                                     self.last_coords.push(current_position);
                                 }
@@ -974,18 +1529,25 @@ impl PioneerBot<'_> {
                             | MoveError::NotImplemented => msg = "what the f!#@??",
                         }
                         println!("Destination is {msg}");
-                        self.next_objective();
-
-                        // if the destination needs to be added to the pins, do so
-                        // this is needed because when randomly exploring the spyglass
+                        if let MoveError::InvalidDestCoordinate | MoveError::NoAvailableMove = e {
+                            self.mark_unreachable();
+                        }
+                        // if the destination needs to be logged as visited, do so
+                        // this is needed because when exploring the spyglass
                         // is set to look for buildings, markets and banks first
                         // and if I let it go to any one of those it finds, there is a large
                         // possibility that it will just go back to the same building every time
                         if let MoveError::AlreadyAtDestination = e {
-                            if discover_new {
-                                self.pins.insert(self.get_coordinate_usize());
+                            self.check_spyglass_hit(world);
+                            if let (true, Some(c)) = (discover_new, self.compass_destination()) {
+                                self.record_visit(world, c, Outcome::Reached);
                             }
                         }
+                        self.next_objective(if let MoveError::AlreadyAtDestination = e {
+                            Reason::DestinationReached
+                        } else {
+                            Reason::DestinationUnavailable
+                        });
                     }
                 }
             }
@@ -999,7 +1561,7 @@ impl PioneerBot<'_> {
                 // if the desired charge level is reached,
                 // decide what to do next
                 if self.get_energy().get_energy_level() >= target_level {
-                    self.next_objective();
+                    self.next_objective(Reason::Charged);
                 }
                 // else pass
             }
@@ -1008,7 +1570,10 @@ impl PioneerBot<'_> {
             // then stays put till morning
             | Sleeping => {
                 if self.place_tent(world).is_ok() {
-                    self.set_next(Praying);
+                    // pray in the morning, unless there is something left to do from yesterday
+                    if self.tasks.len() == 1 {
+                        self.set_next(Praying);
+                    }
                     self.set_objective(Waiting(
                         // if for some reason the robot decides to sleep in the morning, wait till night first and then repeat
                         if let DayTime::Morning = look_at_sky(world).get_time_of_day() {
@@ -1016,7 +1581,7 @@ impl PioneerBot<'_> {
                         } else {
                             DayTime::Morning
                         },
-                    ));
+                    ), Reason::TentPlaced);
                 } else {
                     println!("{}", "Couldn't place the tent".color(Color::BrightRed))
                 }
@@ -1030,13 +1595,25 @@ impl PioneerBot<'_> {
                 // the content and only then use collect all
                 if let Some(direction) =
                     self.face_target(world, true, |tile| tile.content.to_default() == content.to_default()) {
+                    if let Some(c) = self.look_ahead(world, direction.clone()) {
+                        self.record_visit(world, c, Outcome::Gathered);
+                    }
                     let _ = destroy(self, world, direction);
                 }
+                // the content isn't where the robot was sent anymore, don't let the mapper send it there again
+                // (unless the spyglass made it up, in which case it's already being avoided)
+                else if let Some(target) = self.last_destination.take() {
+                    self.record_visit(world, target, Outcome::NothingThere);
+                    if !self.memory.avoids_because(target, Avoid::FalsePositive, self.day) {
+                        self.memory.remember(target, Avoid::Vanished, self.day);
+                    }
+                }
 
                 // collect more content in the area if there is enough space in the backpack
                 // also do not do it with fish because it will bug out greatly
                 if content.to_default() != Content::Fish(0)
-                    && self.get_backpack().get_contents().values().sum::<usize>() < self.get_backpack().get_size() * 4 / 5 {
+                    && self.get_backpack().get_contents().values().sum::<usize>()
+                    < self.config.sell_threshold(self.get_backpack().get_size()) {
                     let mut requirements = HashMap::new();
                     let space_left =
                         self.get_backpack().get_size() - self.get_backpack().get_contents().values().sum::<usize>();
@@ -1048,18 +1625,21 @@ impl PioneerBot<'_> {
                 let next_weather = self.forecast.predict_from_time(0, 24).unwrap_or(WeatherType::Sunny);
 
                 // if there is still space in the backpack and there is no storm incoming, continue the gathering streak
-                if self.get_backpack().get_contents().values().sum::<usize>() < self.get_backpack().get_size() * 4 / 5 {
+                if self.get_backpack().get_contents().values().sum::<usize>()
+                    < self.config.sell_threshold(self.get_backpack().get_size()) {
                     if assisted || (next_weather != WeatherType::TrentinoSnow && next_weather != WeatherType::TropicalMonsoon) {
                         // set the status to MOVING for the next ticks,
                         // in order to move to the next closest area with the target content
-                        if let Ok(c) = self.map.find_closest(world, self, content.clone()) {
-                            let c = swap_coordinates(c.into());
-                            println!("Found {} at ({}, {})", content, c.0, c.1);
-                            self.compass.set_destination(Destination::Coordinate(c));
-                            self.set_objective(Moving(false));
+                        if let Some(c) = self
+                            .find_closest(world, content.clone())
+                            .filter(|c| !self.is_avoided(*c))
+                        {
+                            println!("Found {content} at {c}");
+                            self.compass.set_destination(c.into());
+                            self.set_objective(Moving(false), Reason::DestinationFound);
                         } else {
                             println!("No {} found in the vicinity, need to explore", content);
-                            self.set_objective(Exploring);
+                            self.set_objective(Exploring, Reason::NotFound);
                         }
                         self.set_next(Gathering(content));
                     }
@@ -1067,9 +1647,8 @@ impl PioneerBot<'_> {
                 // otherwise go selling
                 else {
                     println!("{}", "Backpack too full, selling".color(Color::BrightRed));
-                    let sellable_content = self.get_content_to_sell();
-                    self.set_objective(Selling(sellable_content));
-                    self.set_next(Objective::None);
+                    let sellable_content = self.get_content_to_sell(world);
+                    self.set_objective(Selling(sellable_content), Reason::BackpackFull);
                 }
             }
 
@@ -1078,49 +1657,59 @@ impl PioneerBot<'_> {
                 let quantity_held = *self.get_backpack().get_contents().get(&content).unwrap_or(&0);
                 let mut transaction_ok = false;
 
-                // look for market in the vicinity in which to sell the content
+                // look for market in the vicinity in which to sell the content,
+                // with room for all of it unless no known market has that much
+                let needed = self.room_needed(world, Content::Market(0), quantity_held);
                 match self.face_target(world, true,
-                                       |tile| { if let Content::Market(n) = tile.content { n >= quantity_held } else { false } }) {
-                    // if there is no un-depleted market nearby
+                                       |tile| { if let Content::Market(n) = tile.content { n >= needed } else { false } }) {
+                    // if there is no market with enough room nearby
                     | None => {
-                        // the map might still tag as most loaded the depleted market in front of the robot, since it might be the only market discovered
-                        if let Ok(c) = self.map.find_most_loaded(world, self, Content::Market(0)) {
-
-                            // if it's not the case, move towards the newfound market
-                            if !self.bankrupt.contains(&swap_coordinates(c.into())) {
-                                println!("Market found at {:?}", swap_coordinates(c.into()));
-                                self.compass.set_destination(Destination::Coordinate(swap_coordinates(c.into())));
-                                transaction_ok = true;
-                                self.set_next(Selling(content.clone()));
-                                self.set_objective(Moving(true));
-                            }
+                        // the ledger already leaves out the markets known to be depleted
+                        if let Some(c) = self.find_room(world, Content::Market(0), quantity_held) {
+                            println!("Market found at {c}");
+                            self.compass.set_destination(c.into());
+                            transaction_ok = true;
+                            self.set_next(Selling(content.clone()));
+                            self.set_objective(Moving(true), Reason::DestinationFound);
                         }
                     }
                     // if the market is close to the robot
                     | Some(direction) => {
-                        match put(self, world, content.clone(), quantity_held, direction.clone()) {
+                        // the coins come in while the put is carried out
+                        if let Some(c) = self.look_ahead(world, direction.clone()) {
+                            self.prices.start_sale(&content, c);
+                        }
+                        let result = put(self, world, content.clone(), quantity_held, direction.clone());
+                        self.prices.finish_sale(result.as_ref().ok().copied());
+                        match result {
                             | Ok(quantity_sold) => {
+                                if let Some(c) = self.look_ahead(world, direction.clone()) {
+                                    self.ledger.transaction(c, quantity_held, quantity_sold, self.day);
+                                }
                                 // if the robot sold all the content in the backpack
                                 if quantity_sold == quantity_held {
                                     if let (Some(audio), Some(sounds)) = (self.audio.as_mut(), self.sounds.as_ref()) {
                                         let _ = audio.play_audio(&sounds[2]);
                                     }
+                                    if let Some(c) = self.look_ahead(world, direction.clone()) {
+                                        self.record_visit(world, c, Outcome::Sold);
+                                    }
                                     transaction_ok = true;
-                                    self.next_objective();
+                                    self.next_objective(Reason::TransactionDone);
                                 }
                                 // otherwise find another market
                                 else {
                                     if let Some(c) = self.look_ahead(world, direction.clone()) {
-                                        self.bankrupt.insert(c);
+                                        self.record_visit(world, c, Outcome::Depleted);
                                     }
-                                    if let Ok(c) = self.map.find_most_loaded(world, self, Content::Market(0)) {
+                                    if let Some(c) = self.find_room(world, Content::Market(0), quantity_held - quantity_sold) {
                                         // and check again that it's not the same one
-                                        if self.look_ahead(world, direction).unwrap_or((0, 0)) != swap_coordinates(c.into()) {
-                                            println!("Market depleted, new one found at {:?}", swap_coordinates(c.into()));
+                                        if self.look_ahead(world, direction) != Some(c) {
+                                            println!("Market depleted, new one found at {c}");
                                             transaction_ok = true;
-                                            self.compass.set_destination(Destination::Coordinate(c.into()));
+                                            self.compass.set_destination(c.into());
                                             self.set_next(Selling(content.clone()));
-                                            self.set_objective(Moving(true));
+                                            self.set_objective(Moving(true), Reason::Depleted);
                                         }
                                     }
                                 }
@@ -1132,7 +1721,8 @@ impl PioneerBot<'_> {
                                     let _ = audio.play_audio(&sounds[2]);
                                 }
                                 transaction_ok = true;
-                                self.set_objective(Depositing);
+                                // then come back to sell the rest
+                                self.interrupt(Depositing, Reason::TooRich);
                             }
                             | Err(e) => {
                                 eprintln!("{e:?}");
@@ -1143,59 +1733,75 @@ impl PioneerBot<'_> {
                 if !transaction_ok {
                     println!("{}", "No market found".color(Color::BrightRed));
                     self.compass.clear_destination();
-                    self.set_objective(Exploring);
+                    // look for one, and try again after
+                    self.set_next(Selling(content));
+                    self.set_objective(Exploring, Reason::NotFound);
+                }
+            }
+
+            | Crafting(content) => {
+                match craft(self, content.clone()) {
+                    | Ok(_) => println!("Crafted a new {}", content_name(&content)),
+                    | Err(e) => eprintln!("{e:?}"),
                 }
+                self.next_objective(Reason::TaskDone);
             }
 
             | Depositing => {
                 let quantity_held = *self.get_backpack().get_contents().get(&Content::Coin(0)).unwrap_or(&0);
                 let mut transaction_ok = false;
 
-                // look for bank
+                // look for bank, with room for all the coins unless no known bank has that much
+                let needed = self.room_needed(world, Content::Bank(0..0), quantity_held);
                 match self.face_target(world, true,
                                        |tile| {
-                                           if let Content::Bank(range) = &tile.content { range.clone().sum::<usize>() > 0 } else { false }
+                                           matches!(tile.content, Content::Bank(_)) && ledger::capacity(&tile.content).unwrap_or(0) >= needed
                                        }) {
                     | None => {
-                        if let Ok(c) = self.map.find_most_loaded(world, self, Content::Bank(0..0)) {
-                            if !self.bankrupt.contains(&swap_coordinates(c.into())) {
-                                println!("Bank found at {:?}", swap_coordinates(c.into()));
-                                self.compass.set_destination(Destination::Coordinate(swap_coordinates(c.into())));
-                                transaction_ok = true;
-                                self.set_next(Depositing);
-                                self.set_objective(Moving(true));
-                            }
+                        if let Some(c) = self.find_room(world, Content::Bank(0..0), quantity_held) {
+                            println!("Bank found at {c}");
+                            self.compass.set_destination(c.into());
+                            transaction_ok = true;
+                            self.set_next(Depositing);
+                            self.set_objective(Moving(true), Reason::DestinationFound);
                         }
                     }
                     // if the bank is close to the robot
                     | Some(direction) => {
                         match put(self, world, Content::Coin(0), quantity_held, direction.clone()) {
                             | Ok(quantity_deposited) => {
+                                if let Some(c) = self.look_ahead(world, direction.clone()) {
+                                    self.ledger.transaction(c, quantity_held, quantity_deposited, self.day);
+                                }
                                 if quantity_deposited == quantity_held {
                                     if let (Some(audio), Some(sounds)) = (self.audio.as_mut(), self.sounds.as_ref()) {
                                         let _ = audio.play_audio(&sounds[2]);
                                     }
+                                    if let Some(c) = self.look_ahead(world, direction) {
+                                        self.record_visit(world, c, Outcome::Deposited);
+                                    }
                                     transaction_ok = true;
-                                    self.next_objective();
+                                    self.next_objective(Reason::TransactionDone);
                                 } else {
                                     // the bank is depleted
                                     if let Some(c) = self.look_ahead(world, direction) {
-                                        self.bankrupt.insert(c);
+                                        self.record_visit(world, c, Outcome::Depleted);
                                     }
-                                    if let Ok(c) = self.map.find_most_loaded(world, self, Content::Market(0)) {
-                                        if !self.bankrupt.contains(&swap_coordinates(c.into())) {
-                                            println!("Bank depleted, new one found at {:?}", swap_coordinates(c.into()));
-                                            transaction_ok = true;
-                                            self.compass.set_destination(Destination::Coordinate(c.into()));
-                                            self.set_next(Depositing);
-                                            self.set_objective(Moving(true));
-                                        }
+                                    if let Some(c) = self.find_room(world, Content::Bank(0..0), quantity_held - quantity_deposited) {
+                                        println!("Bank depleted, new one found at {c}");
+                                        transaction_ok = true;
+                                        self.compass.set_destination(c.into());
+                                        self.set_next(Depositing);
+                                        self.set_objective(Moving(true), Reason::Depleted);
                                     }
                                 }
                             }
                             | Err(LibError::NotEnoughEnergy) => {
-                                self.set_next(Depositing);
-                                self.set_objective(Charging(self.get_energy().get_energy_level() + 100));
+                                transaction_ok = true;
+                                self.interrupt(
+                                    Charging(self.get_energy().get_energy_level() + self.config.deposit_recharge),
+                                    Reason::LowEnergy,
+                                );
                             }
                             | Err(e) => {
                                 eprintln!("{e:?}");
@@ -1206,25 +1812,32 @@ impl PioneerBot<'_> {
                 if !transaction_ok {
                     println!("{}", "No Bank found".color(Color::BrightRed));
                     self.compass.clear_destination();
-                    self.set_objective(Exploring);
+                    self.set_next(Depositing);
+                    self.set_objective(Exploring, Reason::NotFound);
                 }
             }
 
             | Exploring => {
+                // the known map hasn't changed since there was no frontier left, don't waste the spyglass
+                if self.explored_at.is_some() && self.explored_at == robot_map(world).map(|map| known_tiles(&map)) {
+                    println!("{}", "Nothing left to explore".color(Color::BrightRed));
+                    self.stop_exploring();
+                    return;
+                }
                 let dim = robot_map(world).unwrap().len();
                 let mut mark_visited = false;
                 let mut target_content = Vec::new();
 
                 // there probably was a better way to do this but
                 // doing something like
-                // if let Gathering(content_to_gather) = self.next.clone() {
+                // if let Gathering(content_to_gather) = self.tasks.next() {
                 //      let stops_when = |tile| {
                 //            tile.content.to_default() = content_to_gather.to_default()
                 //      }
                 // }
                 // isn't possible
                 let mut stops_when: fn(&Tile) -> bool = |_| false;
-                if let Gathering(content) = &self.next {
+                if let Gathering(content) = &self.tasks.next() {
                     match content {
                         | Content::Rock(_) => {
                             stops_when = |tile| {
@@ -1270,7 +1883,7 @@ impl PioneerBot<'_> {
                 let mut spyglass = Spyglass::new(
                     self.get_coordinate().get_row(),
                     self.get_coordinate().get_col(),
-                    dim / 2,
+                    dim / self.config.exploring_spyglass_divisor,
                     dim,
                     Some(self.get_energy().get_energy_level() / 2),
                     true,
@@ -1278,30 +1891,38 @@ impl PioneerBot<'_> {
                     stops_when,
                 );
 
-                let mut destination_found = false;
-                if let SpyglassResult::Stopped(vec) = spyglass.new_discover(self, world) {
+                self.refresh_towns(world);
+                let known = robot_map(world).unwrap_or_default();
+                let result = spyglass.new_discover(self, world);
+                let (hits, stats) = self.spyglass.verify(
+                    result,
+                    &known,
+                    self.position(),
+                    |tile| target_content.contains(&tile.content.to_default()),
+                );
+                println!("Spyglass: {stats} ({})", self.spyglass);
 
-                    // for some reason the spyglass will sometimes include tiles that do not match the criteria
-                    for (tile, row, col) in vec.iter() {
-                        if !mark_visited
-                            || (mark_visited && !self.pins.contains(&(*row, *col)))
-                            && target_content.contains(&tile.content.to_default())
-                        {
-                            println!(
-                                "{}",
-                                format!("Found {} at ({}, {}) with my spyglass", tile.content, row, col)
-                                    .color(Color::BrightGreen)
-                            );
-                            destination_found = true;
-                            self.compass.set_destination(Destination::Coordinate((*row, *col)));
-                            self.set_objective(Moving(mark_visited));
-                            break;
-                        }
+                // when looking for towns, the places visited recently don't count
+                let hit = hits.into_iter().find(|(_, c)| {
+                    if !mark_visited || !self.visits.visited_recently(*c, self.day) {
+                        return true;
                     }
-                }
-                if !destination_found {
-                    println!("Nothing found with the spyglass, setting random destination");
-                    self.set_random_destination(world);
+                    if let Some(visit) = self.visits.get(*c) {
+                        println!(
+                            "Skipping {c}, visited between day {} and {} ({}: {})",
+                            visit.first_day, visit.last_day, visit.found, visit.outcome
+                        );
+                    }
+                    false
+                });
+                if let Some(hit) = hit.as_ref() {
+                    println!("{}", format!("Found {} at {} with my spyglass", hit.0.content, hit.1).color(Color::BrightGreen));
+                    self.spyglass.follow(hit, self.day);
+                    self.compass.set_destination(hit.1.into());
+                    self.set_objective(Moving(mark_visited), Reason::SpyglassHit);
+                } else {
+                    println!("Nothing found with the spyglass, heading to the frontier");
+                    self.set_frontier_destination(world);
                 }
             }
 
@@ -1318,8 +1939,8 @@ impl PioneerBot<'_> {
                             .sum::<usize>()
                     })
                     .sum::<usize>()
-                    // check that the sum is > than 75% of the area of the map
-                    > robot_map(world).unwrap().len().pow(2) * 3 / 4
+                    // check that the sum is > than the configured fraction of the area of the map
+                    > (robot_map(world).unwrap().len().pow(2) as f32 * self.config.end_coverage) as usize
 
                     // this one checks that there are no more active markets or banks
                     && Map::collection(&world)
@@ -1328,12 +1949,9 @@ impl PioneerBot<'_> {
                     // filter the HashMap for Markets and Banks only
                     .filter(|(k, v)|
                         if **k == mem::discriminant(&Content::Market(0)) || **k == mem::discriminant(&Content::Bank(0..0)) {
-                            // filter the vector of coordinates to find the ones that aren't in the bankrupt list
+                            // filter the vector of coordinates to find the ones that aren't depleted
                             !v.iter()
-                                .filter(|(c, _)|
-                                    !self
-                                        .bankrupt
-                                        .contains(&swap_coordinates((*c).into())))
+                                .filter(|(c, _)| !self.ledger.is_exhausted(XY::from_mapper((*c).into()).into(), self.day))
                                 .collect::<Vec<_>>()
                                 .is_empty()
                         } else { false })
@@ -1345,10 +1963,10 @@ impl PioneerBot<'_> {
                     self.handle_event(Event::Terminated);
                 }
                 // otherwise go on with any next objective
-                else if let Objective::None = self.next {
-                    self.set_objective(Praying);
+                else if self.tasks.is_empty() {
+                    self.set_objective(Praying, Reason::Idle);
                 } else {
-                    self.next_objective();
+                    self.next_objective(Reason::Idle);
                 }
             }
         }
@@ -1393,7 +2011,7 @@ impl PioneerBot<'_> {
                     let _ = Spyglass::new(
                         self.get_coordinate().get_row(),
                         self.get_coordinate().get_col(),
-                        10,
+                        self.config.manual_spyglass_radius,
                         robot_map(world).unwrap().len(),
                         None,
                         false,
@@ -1402,12 +2020,16 @@ impl PioneerBot<'_> {
                     ).new_discover(self, world);
                 }
                 2 /* sell */ => {
-                    let content = self.get_content_to_sell();
+                    let content = self.get_content_to_sell(world);
                     if let Some(direction) = self.face_target(world, false,
                                                               |tile| if let Content::Market(_) = tile.content { true } else { false })
                     {
-                        let quantity = self.get_backpack().get_contents().get(&content).unwrap_or(&0usize);
-                        let _ = put(self, world, content, *quantity, direction);
+                        let quantity = *self.get_backpack().get_contents().get(&content).unwrap_or(&0usize);
+                        if let Some(c) = self.look_ahead(world, direction.clone()) {
+                            self.prices.start_sale(&content, c);
+                        }
+                        let result = put(self, world, content, quantity, direction);
+                        self.prices.finish_sale(result.ok());
                     }
                 }
                 1 /* deposit */ => {
@@ -1432,9 +2054,13 @@ impl PioneerBot<'_> {
 
 impl Runnable for PioneerBot<'_> {
     fn process_tick(&mut self, world: &mut World) {
+        // the current task is one tick closer to running out of budget
+        self.tick += 1;
+        self.tasks.spend_tick();
+
         // add some delay if the gui is not in use
         if self.gui.is_none() {
-            std::thread::sleep(std::time::Duration::from_millis(500));
+            std::thread::sleep(std::time::Duration::from_millis(self.config.tick_delay_ms));
         }
 
         // try to reconnect the pilot, in case the pico got unplugged
@@ -1468,7 +2094,7 @@ impl Runnable for PioneerBot<'_> {
         self.score = get_score(world);
 
         // easter egg
-        if random::<u32>() % 1000 == 0 {
+        if self.rng.gen::<u32>() % 1000 == 0 {
             if let (Some(audio), Some(sounds)) = (self.audio.as_mut(), self.sounds.as_ref()) {
                 let _ = audio.play_audio(&sounds[3]);
             }
@@ -1491,18 +2117,26 @@ impl Runnable for PioneerBot<'_> {
                 *self.running.borrow_mut() = false;
             }
             | Event::TimeChanged(e) => {
+                self.time_of_day = e.get_time_of_day();
                 self.gui.as_mut().map(|gui| {
                     gui.update_weather(e.get_weather_condition());
                     gui.update_time_of_day(e.get_time_of_day());
                 });
+                self.costs.set_weather(e.get_weather_condition());
                 self.forecast.process_event(&Event::TimeChanged(e));
             }
             | Event::DayChanged(_) => {
+                self.day += 1;
+                self.memory.forget_expired(self.day);
+                self.spyglass.forget_expired(self.day);
                 println!("Score: {}", self.score);
                 self.pilot.as_mut().map(|pilot| pilot.put_score(self.score));
             }
             | Event::EnergyRecharged(_) => {}
-            | Event::EnergyConsumed(_) => {}
+            | Event::EnergyConsumed(energy) => {
+                self.tasks.spend_energy(energy);
+                self.step_energy += energy;
+            }
             | Event::Moved(_, coords) => {
                 if self.last_coords.len() > 8 {
                     self.last_coords.remove(0);
@@ -1527,6 +2161,9 @@ impl Runnable for PioneerBot<'_> {
                     "{}",
                     format!("-> {quantity} {content} added to backpack").color(Color::BrightCyan)
                 );
+                if let Content::Coin(_) = content {
+                    self.prices.coins_received(quantity);
+                }
             }
             | Event::RemovedFromBackpack(content, quantity) => {
                 println!(
@@ -1562,31 +2199,176 @@ impl Runnable for PioneerBot<'_> {
     }
 }
 
-// I hid these functions down here because I'm ashamed of them
+#[cfg(test)]
+mod tests {
+    use robotics_lib::runner::Runner;
+    use robotics_lib::world::environmental_conditions::EnvironmentalConditions;
+    use robotics_lib::world::tile::TileType;
+    use robotics_lib::world::world_generator::{Generator, World as GeneratedWorld};
+
+    use super::*;
+    use crate::strategy;
+
+    // tiles of a world: walls where the pattern has a '#', active teleports where it has a 'T', grass elsewhere
+    fn tiles(pattern: &[&str]) -> Vec<Vec<Tile>> {
+        pattern
+            .iter()
+            .map(|row| {
+                row.chars()
+                    .map(|c| Tile {
+                        tile_type: match c {
+                            | '#' => TileType::Wall,
+                            | 'T' => TileType::Teleport(true),
+                            | _ => TileType::Grass,
+                        },
+                        content: Content::None,
+                        elevation: 0,
+                    })
+                    .collect()
+            })
+            .collect()
+    }
 
-// swap the coordinates
-// NLA compass, spyglass and robotics_lib all use (row, column) to index the map
-// while tile/resource mapper does the opposite
-fn swap_coordinates(c: (usize, usize)) -> (usize, usize) {
-    // I literally cannot believe this needs to exist
-    (c.1, c.0)
-}
+    struct TestWorld {
+        tiles: Vec<Vec<Tile>>,
+        spawn: (usize, usize),
+    }
+
+    impl Generator for TestWorld {
+        fn gen(&mut self) -> GeneratedWorld {
+            let conditions = EnvironmentalConditions::new(&[WeatherType::Sunny], 15, 8).unwrap();
+            (self.tiles.clone(), self.spawn, conditions, 100., None)
+        }
+    }
+
+    // hands the bot and the world to the test for a single tick, instead of letting the bot decide
+    struct Harness {
+        bot: PioneerBot<'static>,
+        test: Box<dyn FnMut(&mut PioneerBot<'static>, &mut World)>,
+        done: Rc<RefCell<bool>>,
+    }
+
+    impl Runnable for Harness {
+        fn process_tick(&mut self, world: &mut World) {
+            (self.test)(&mut self.bot, world);
+            *self.done.borrow_mut() = true;
+        }
+
+        fn handle_event(&mut self, event: Event) {
+            self.bot.handle_event(event)
+        }
+
+        fn get_energy(&self) -> &Energy {
+            self.bot.get_energy()
+        }
+
+        fn get_energy_mut(&mut self) -> &mut Energy {
+            self.bot.get_energy_mut()
+        }
+
+        fn get_coordinate(&self) -> &Coordinate {
+            self.bot.get_coordinate()
+        }
+
+        fn get_coordinate_mut(&mut self) -> &mut Coordinate {
+            self.bot.get_coordinate_mut()
+        }
+
+        fn get_backpack(&self) -> &BackPack {
+            self.bot.get_backpack()
+        }
+
+        fn get_backpack_mut(&mut self) -> &mut BackPack {
+            self.bot.get_backpack_mut()
+        }
+    }
+
+    fn run(
+        tiles: Vec<Vec<Tile>>,
+        spawn: (usize, usize),
+        test: impl FnMut(&mut PioneerBot<'static>, &mut World) + 'static,
+    ) {
+        let config = PioneerConfig {
+            tick_delay_ms: 0,
+            pickup_chance: 0.,
+            spyglass_chance: 0.,
+            seed: Some(0),
+            ..PioneerConfig::default()
+        };
+        let done = Rc::new(RefCell::new(false));
+        let harness = Harness {
+            bot: PioneerBot::new(false, false, strategy::by_name("utility").unwrap(), None, config),
+            test: Box::new(test),
+            done: Rc::clone(&done),
+        };
+        let mut runner = match Runner::new(Box::new(harness), &mut TestWorld { tiles, spawn }) {
+            | Ok(runner) => runner,
+            | Err(e) => panic!("can't create the world: {e:?}"),
+        };
+        let _ = runner.game_tick();
+        assert!(*done.borrow(), "the test didn't run");
+    }
+
+    // lets the bot see the whole world, so that the pathfinder knows about the walls
+    fn discover_all(bot: &mut PioneerBot, world: &mut World) {
+        let dim = robot_map(world).unwrap().len();
+        let RowCol { row, col } = bot.position();
+        let _ = Spyglass::new(row, col, dim, dim, None, false, 0.5, |_| false).new_discover(bot, world);
+    }
 
-// converts the u8 readings from the serial port to
-// the corresponding objective
-impl From<u8> for Objective {
-    fn from(value: u8) -> Self {
-        match value {
-            | 1 => Charging(750),
-            | 2 => Selling(Content::Fish(0)),
-            | 3 => Selling(Content::Tree(0)),
-            | 4 => Selling(Content::Rock(0)),
-            | 5 => Gathering(Content::Fish(0)),
-            | 6 => Gathering(Content::Tree(0)),
-            | 7 => Gathering(Content::Rock(0)),
-            | 8 => Depositing,
-            | 9 => Exploring,
-            | _ => Objective::None,
-        }
-    }
-}
\ No newline at end of file
+    // follows the current objective until the bot gets to the goal, or gives up after a while
+    fn walk_to(bot: &mut PioneerBot, world: &mut World, goal: RowCol) {
+        for _ in 0..30 {
+            if bot.position() == goal {
+                break;
+            }
+            bot.auto_pilot(world, false);
+        }
+        assert_eq!(bot.position(), goal);
+    }
+
+    #[test]
+    fn pathfinder_takes_over_when_the_compass_is_lost() {
+        // the goal is right above the bot, but the wall is open only at the bottom
+        let world = tiles(&[
+            ".....",
+            ".###.",
+            ".#.#.",
+            ".#.#.",
+            ".....",
+        ]);
+        let goal = RowCol::new(0, 2);
+        run(world, (2, 2), move |bot, world| {
+            discover_all(bot, world);
+            bot.compass.set_destination(goal.into());
+            bot.set_objective(Moving(false), Reason::DestinationFound);
+
+            // what the Moving arm does when the compass can't find a move
+            assert!(bot.reroute(world), "no route around the wall");
+            walk_to(bot, world, goal);
+        });
+    }
+
+    #[test]
+    fn teleports_make_for_shortcuts() {
+        // the wall splits the world in two, only the teleports link the two sides
+        let world = tiles(&[
+            "...#...",
+            "...#...",
+            "...#...",
+            "..T#T..",
+            "...#...",
+            "...#...",
+            "...#...",
+        ]);
+        let goal = RowCol::new(3, 5);
+        run(world, (3, 1), move |bot, world| {
+            discover_all(bot, world);
+            bot.compass.set_destination(goal.into());
+            bot.set_objective(Moving(false), Reason::DestinationFound);
+            bot.prepare_trip(world);
+            assert!(bot.teleports.contains(&RowCol::new(3, 4)));
+            walk_to(bot, world, goal);
+        });
+    }
+}
@@ -0,0 +1,346 @@
+use rand::rngs::StdRng;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt::{Display, Formatter};
+
+use robotics_lib::world::environmental_conditions::{DayTime, WeatherType};
+use robotics_lib::world::tile::Content;
+
+use crate::objective::{content_name, Objective};
+use crate::objective::Objective::{Charging, Crafting, Depositing, Exploring, Gathering, Selling, Sleeping, Waiting};
+use crate::strategy::{Snapshot, Strategy, DESTROY_ENERGY, MAX_ENERGY, SELLABLE, SPYGLASS_ENERGY};
+
+// goal oriented action planning: the actions of the robot are described by what they need and what they change
+// in an abstract state (backpack, coins, energy and kind of place the robot is at), and the planner searches for
+// the cheapest sequence of actions that reaches a goal. The plan is then turned into objectives for the task stack
+
+// energy gained by resting, and the cost given to the time it takes
+const REST_ENERGY: usize = 200;
+const REST_COST: usize = 20;
+// wood needed to craft a tent
+const TENT_WOOD: usize = 2;
+// distance assumed for places found with the spyglass
+const EXPLORE_DISTANCE: usize = 20;
+// the search gives up after looking at this many states
+const MAX_EXPANSIONS: usize = 20_000;
+
+// kind of place the robot can be at
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Place {
+    Start,
+    Rock,
+    Tree,
+    Fish,
+    Market,
+    Bank,
+}
+
+impl Place {
+    fn of(content: &Content) -> Place {
+        match content {
+            | Content::Rock(_) => Place::Rock,
+            | Content::Tree(_) => Place::Tree,
+            | Content::Fish(_) => Place::Fish,
+            | Content::Market(_) => Place::Market,
+            | _ => Place::Bank,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct State {
+    // amount of each sellable content, in the same order as SELLABLE
+    held: [usize; 3],
+    coins: usize,
+    deposited: usize,
+    tents: usize,
+    energy: usize,
+    position: Place,
+    // places the robot knows how to reach, in the same order as the variants of Place (Start excluded)
+    known: [bool; 5],
+}
+
+impl State {
+    fn total(&self) -> usize {
+        self.held.iter().sum::<usize>() + self.coins + self.tents
+    }
+
+    fn knows(&self, place: Place) -> bool {
+        place == Place::Start || self.known[place as usize - 1]
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Action {
+    Go(Place),
+    Destroy(Content),
+    Put(Content),
+    Craft,
+    Spyglass(Place),
+    Rest,
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            | Action::Go(place) => write!(f, "go to {place:?}"),
+            | Action::Destroy(content) => write!(f, "destroy {}", content_name(content)),
+            | Action::Put(content) => write!(f, "put {}", content_name(content)),
+            | Action::Craft => write!(f, "craft a tent"),
+            | Action::Spyglass(place) => write!(f, "look for {place:?} with the spyglass"),
+            | Action::Rest => write!(f, "rest"),
+        }
+    }
+}
+
+// what the plan has to achieve
+pub struct Goal {
+    pub deposited: usize,
+    pub tent: bool,
+}
+
+impl Goal {
+    fn reached(&self, state: &State) -> bool {
+        state.deposited >= self.deposited && (!self.tent || state.tents > 0)
+    }
+}
+
+pub struct Planner {
+    // distance of the closest known place of each kind
+    distances: HashMap<Place, usize>,
+    backpack_size: usize,
+    // the robot gathers until the backpack holds this much
+    gather_up_to: usize,
+    // energy of a single step
+    step_energy: usize,
+    // coins for a unit of each sellable content, in the same order as SELLABLE
+    prices: [f32; 3],
+}
+
+impl Planner {
+    pub fn new(snapshot: &Snapshot) -> Self {
+        Planner {
+            distances: snapshot
+                .distances
+                .iter()
+                .filter(|(content, _)| !matches!(content, Content::Building))
+                .map(|(content, distance)| (Place::of(content), *distance))
+                .collect(),
+            backpack_size: snapshot.backpack_size,
+            gather_up_to: snapshot.config.sell_threshold(snapshot.backpack_size),
+            step_energy: (snapshot.step_energy.round() as usize).max(1),
+            prices: SELLABLE.map(|content| snapshot.price(&content)),
+        }
+    }
+
+    // abstract state the robot is in right now
+    pub fn state(&self, snapshot: &Snapshot) -> State {
+        let quantity = |content: &Content| *snapshot.backpack.get(content).unwrap_or(&0);
+        let mut known = [false; 5];
+        for place in self.distances.keys() {
+            known[*place as usize - 1] = true;
+        }
+        State {
+            held: SELLABLE.map(|content| quantity(&content)),
+            coins: quantity(&Content::Coin(0)),
+            deposited: 0,
+            tents: quantity(&Content::JollyBlock(0)),
+            energy: snapshot.energy,
+            position: Place::Start,
+            known,
+        }
+    }
+
+    // every action possible from a state, with the resulting state and the cost
+    fn actions(&self, state: &State) -> Vec<(Action, State, usize)> {
+        let mut actions = Vec::new();
+
+        // go: the place must be known and the robot must have enough energy to reach it
+        // (distances are only known from where the robot is now, so they are used from anywhere)
+        for place in [Place::Rock, Place::Tree, Place::Fish, Place::Market, Place::Bank] {
+            if place != state.position && state.knows(place) {
+                let energy = *self.distances.get(&place).unwrap_or(&EXPLORE_DISTANCE) * self.step_energy;
+                if energy <= state.energy {
+                    let mut next = state.clone();
+                    next.position = place;
+                    next.energy -= energy;
+                    actions.push((Action::Go(place), next, energy));
+                }
+            }
+        }
+
+        for (i, content) in SELLABLE.iter().enumerate() {
+            // destroy: gathers the content until the backpack is full enough to go selling
+            let amount = self.gather_up_to.saturating_sub(state.total());
+            let energy = amount * DESTROY_ENERGY as usize;
+            if state.position == Place::of(content) && amount > 0 && energy <= state.energy {
+                let mut next = state.clone();
+                next.held[i] += amount;
+                next.energy -= energy;
+                actions.push((Action::Destroy(content.clone()), next, energy));
+            }
+
+            // put at the market: sells all the content held
+            if state.position == Place::Market && state.held[i] > 0 && state.energy > 0 {
+                let mut next = state.clone();
+                next.coins += (state.held[i] as f32 * self.prices[i]).round() as usize;
+                next.held[i] = 0;
+                next.energy -= 1;
+                actions.push((Action::Put(content.clone()), next, 1));
+            }
+        }
+
+        // put at the bank: deposits all the coins
+        if state.position == Place::Bank && state.coins > 0 && state.energy > 0 {
+            let mut next = state.clone();
+            next.deposited += state.coins;
+            next.coins = 0;
+            next.energy -= 1;
+            actions.push((Action::Put(Content::Coin(0)), next, 1));
+        }
+
+        // craft a tent out of wood
+        if state.held[1] >= TENT_WOOD && state.total() < self.backpack_size && state.energy > 0 {
+            let mut next = state.clone();
+            next.held[1] -= TENT_WOOD;
+            next.tents += 1;
+            next.energy -= 1;
+            actions.push((Action::Craft, next, 1));
+        }
+
+        // use the spyglass to find a place that isn't known yet
+        for place in [Place::Rock, Place::Tree, Place::Fish, Place::Market, Place::Bank] {
+            if !state.knows(place) && SPYGLASS_ENERGY as usize <= state.energy {
+                let mut next = state.clone();
+                next.known[place as usize - 1] = true;
+                next.energy -= SPYGLASS_ENERGY as usize;
+                actions.push((Action::Spyglass(place), next, SPYGLASS_ENERGY as usize));
+            }
+        }
+
+        // rest to recover energy
+        if state.energy < MAX_ENERGY {
+            let mut next = state.clone();
+            next.energy = (state.energy + REST_ENERGY).min(MAX_ENERGY);
+            actions.push((Action::Rest, next, REST_COST));
+        }
+
+        actions
+    }
+
+    // searches the cheapest sequence of actions that reaches the goal (uniform cost search)
+    pub fn plan(&self, start: State, goal: &Goal) -> Option<Vec<Action>> {
+        // every state found so far, with the action that led to it from its parent
+        let mut states: Vec<(State, Option<(usize, Action)>)> = vec![(start.clone(), None)];
+        let mut best = HashMap::from([(start, 0usize)]);
+        let mut frontier = BinaryHeap::from([Reverse((0usize, 0usize))]);
+        let mut expansions = 0;
+
+        while let Some(Reverse((cost, index))) = frontier.pop() {
+            let state = states[index].0.clone();
+            if best.get(&state).map(|c| *c < cost).unwrap_or(false) {
+                continue;
+            }
+
+            if goal.reached(&state) {
+                // walk back to the start to get the actions in order
+                let mut plan = Vec::new();
+                let mut current = index;
+                while let Some((parent, action)) = states[current].1.clone() {
+                    plan.push(action);
+                    current = parent;
+                }
+                plan.reverse();
+                return Some(plan);
+            }
+
+            expansions += 1;
+            if expansions > MAX_EXPANSIONS {
+                return None;
+            }
+
+            for (action, next, step) in self.actions(&state) {
+                let next_cost = cost + step;
+                if best.get(&next).map(|c| next_cost < *c).unwrap_or(true) {
+                    best.insert(next.clone(), next_cost);
+                    states.push((next, Some((index, action))));
+                    frontier.push(Reverse((next_cost, states.len() - 1)));
+                }
+            }
+        }
+        None
+    }
+}
+
+// turns a plan into the objectives the robot will carry out one after the other
+// going somewhere is left to the objectives themselves, as each one already looks for its destination
+pub fn objectives(plan: &[Action], start: &State) -> Vec<Objective> {
+    let mut energy = start.energy;
+    let mut objectives = Vec::new();
+    for action in plan {
+        match action {
+            | Action::Go(_) => {}
+            | Action::Destroy(content) => objectives.push(Gathering(content.clone())),
+            | Action::Put(Content::Coin(_)) => objectives.push(Depositing),
+            | Action::Put(content) => objectives.push(Selling(content.clone())),
+            | Action::Craft => objectives.push(Crafting(Content::JollyBlock(0))),
+            | Action::Spyglass(_) => objectives.push(Exploring),
+            | Action::Rest => {
+                energy = (energy + REST_ENERGY).min(MAX_ENERGY);
+                objectives.push(Charging(energy));
+            }
+        }
+    }
+    objectives
+}
+
+// strategy that plans the whole day to deposit as many coins as the robot can reasonably earn,
+// crafting a tent along the way if it doesn't have one
+pub struct Goap;
+
+impl Strategy for Goap {
+    fn decide(&mut self, snapshot: &Snapshot, rng: &mut StdRng) -> Objective {
+        self.plan(snapshot, rng).into_iter().next().unwrap_or(Exploring)
+    }
+
+    fn plan(&mut self, snapshot: &Snapshot, _rng: &mut StdRng) -> Vec<Objective> {
+        if let WeatherType::TrentinoSnow | WeatherType::TropicalMonsoon = snapshot.weather {
+            println!("The weather today is {:?}", snapshot.weather);
+            return vec![Sleeping];
+        }
+        if let Some(weather @ (WeatherType::TrentinoSnow | WeatherType::TropicalMonsoon)) = snapshot.forecast.as_ref() {
+            println!("The weather tomorrow is {weather:?}");
+            print!("Decided to reach shelter from tomorrow's storm and ");
+            return vec![Waiting(DayTime::Night)];
+        }
+
+        let planner = Planner::new(snapshot);
+        let start = planner.state(snapshot);
+
+        // deposit the coins held and the ones the content in the backpack is worth,
+        // plus a backpack worth of the cheapest content if it's getting empty
+        let worth = start.held.iter().zip(planner.prices).map(|(n, price)| (*n as f32 * price).round() as usize).sum::<usize>();
+        let mut deposit = start.coins + worth;
+        if start.total() <= snapshot.config.gather_threshold(snapshot.backpack_size) {
+            deposit += snapshot.config.sell_threshold(snapshot.backpack_size).saturating_sub(start.total());
+        }
+        let goal = Goal {
+            deposited: deposit.max(1),
+            tent: start.tents == 0,
+        };
+
+        match planner.plan(start.clone(), &goal) {
+            | Some(plan) => {
+                println!("Planned to deposit {} coins{}:", goal.deposited, if goal.tent { " and craft a tent" } else { "" });
+                for action in plan.iter() {
+                    println!("  {action}");
+                }
+                objectives(&plan, &start)
+            }
+            | None => {
+                println!("Couldn't find a plan, decided to explore");
+                vec![Exploring]
+            }
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use std::collections::HashMap;
+
+use robotics_lib::world::tile::Content;
+
+use crate::coords::RowCol;
+use crate::strategy::{default_price, SELLABLE};
+
+// coins the markets actually give for each content, learned from every sale:
+// a sale starts right before the put, the coins come in through the AddedToBackpack events
+// it triggers, and it's closed once the put says how much was sold
+
+// a sale that is being carried out
+struct Sale {
+    content: Content,
+    market: RowCol,
+    coins: usize,
+}
+
+#[derive(Default)]
+pub struct PriceModel {
+    // coins received and units sold, for each content at each market
+    sales: HashMap<(Content, RowCol), (usize, usize)>,
+    pending: Option<Sale>,
+}
+
+impl PriceModel {
+    pub fn new() -> Self {
+        PriceModel::default()
+    }
+
+    pub fn start_sale(&mut self, content: &Content, market: RowCol) {
+        self.pending = Some(Sale {
+            content: content.to_default(),
+            market,
+            coins: 0,
+        });
+    }
+
+    pub fn coins_received(&mut self, coins: usize) {
+        if let Some(sale) = self.pending.as_mut() {
+            sale.coins += coins;
+        }
+    }
+
+    // closes the sale with the quantity the market took, if any
+    pub fn finish_sale(&mut self, sold: Option<usize>) {
+        let (Some(sale), Some(sold)) = (self.pending.take(), sold) else {
+            return;
+        };
+        if sold == 0 {
+            return;
+        }
+        let (coins, units) = self.sales.entry((sale.content.clone(), sale.market)).or_insert((0, 0));
+        *coins += sale.coins;
+        *units += sold;
+        println!(
+            "Sold {sold} {} at {} for {} coins, {:.2} each so far there",
+            sale.content,
+            sale.market,
+            sale.coins,
+            *coins as f32 / *units as f32
+        );
+    }
+
+    // coins for a unit of the content, across all the markets
+    pub fn price(&self, content: &Content) -> f32 {
+        let content = content.to_default();
+        let (coins, units) = self
+            .sales
+            .iter()
+            .filter(|((c, _), _)| *c == content)
+            .fold((0, 0), |(coins, units), (_, (c, u))| (coins + c, units + u));
+        match units {
+            | 0 => default_price(&content),
+            | _ => coins as f32 / units as f32,
+        }
+    }
+
+    // coins for a unit of the content at a specific market, or across all of them if it was never sold there
+    pub fn price_at(&self, content: &Content, market: RowCol) -> f32 {
+        match self.sales.get(&(content.to_default(), market)) {
+            | Some((coins, units)) if *units > 0 => *coins as f32 / *units as f32,
+            | _ => self.price(content),
+        }
+    }
+
+    // learned prices of every sellable content, for the strategies
+    pub fn prices(&self) -> HashMap<Content, f32> {
+        SELLABLE.iter().map(|content| (content.clone(), self.price(content))).collect()
+    }
+}
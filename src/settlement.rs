@@ -0,0 +1,200 @@
+use std::fmt::{Display, Formatter};
+
+use robotics_lib::world::tile::{Content, Tile};
+
+use crate::config::PioneerConfig;
+use crate::coords::RowCol;
+
+// towns are groups of buildings, markets and banks close to each other in the known map
+
+// names given to towns in the order they are found, with a number once they run out
+const NAMES: [&str; 12] = [
+    "Ashford",
+    "Brindle",
+    "Cobbleton",
+    "Dunmore",
+    "Eastwick",
+    "Fallowmere",
+    "Glenrock",
+    "Harrowgate",
+    "Ivydale",
+    "Juniper Hollow",
+    "Kingsbarrow",
+    "Lindenfield",
+];
+
+// what a town has to offer
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Service {
+    Shelter,
+    Market,
+    Bank,
+}
+
+impl Service {
+    fn of(content: &Content) -> Option<Service> {
+        match content {
+            | Content::Building => Some(Service::Shelter),
+            | Content::Market(_) => Some(Service::Market),
+            | Content::Bank(_) => Some(Service::Bank),
+            | _ => None,
+        }
+    }
+}
+
+impl Display for Service {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            | Service::Shelter => write!(f, "shelter"),
+            | Service::Market => write!(f, "market"),
+            | Service::Bank => write!(f, "bank"),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Town {
+    pub name: String,
+    // tiles holding a building, a market or a bank
+    pub places: Vec<(RowCol, Service)>,
+    pub centroid: RowCol,
+}
+
+impl Town {
+    pub fn services(&self) -> Vec<Service> {
+        let mut services = Vec::new();
+        for (_, service) in self.places.iter() {
+            if !services.contains(service) {
+                services.push(*service);
+            }
+        }
+        services
+    }
+
+    // place of the town closest to a location, offering the service if there is one
+    pub fn closest(&self, from: RowCol, service: Option<Service>) -> Option<RowCol> {
+        self.places
+            .iter()
+            .filter(|(_, s)| service.map(|service| *s == service).unwrap_or(true))
+            .map(|(c, _)| *c)
+            .min_by_key(|c| from.distance(*c))
+    }
+}
+
+impl Display for Town {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let services = self.services().iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        write!(f, "{} around {} ({})", self.name, self.centroid, services.join(", "))
+    }
+}
+
+pub struct Settlements {
+    towns: Vec<Town>,
+    // places this close to each other (in both directions) belong to the same town
+    radius: usize,
+    // names handed out so far
+    named: usize,
+}
+
+impl Settlements {
+    pub fn new(config: &PioneerConfig) -> Self {
+        Settlements {
+            towns: Vec::new(),
+            radius: config.town_radius,
+            named: 0,
+        }
+    }
+
+    pub fn towns(&self) -> &[Town] {
+        &self.towns
+    }
+
+    fn new_name(&mut self) -> String {
+        let name = match self.named / NAMES.len() {
+            | 0 => NAMES[self.named].to_string(),
+            | n => format!("{} {}", NAMES[self.named % NAMES.len()], n + 1),
+        };
+        self.named += 1;
+        name
+    }
+
+    // groups the places of the known map into towns again, keeping the names of the towns already known
+    // returns whether anything changed
+    pub fn update(&mut self, map: &[Vec<Option<Tile>>]) -> bool {
+        let mut places = Vec::new();
+        for (row, tiles) in map.iter().enumerate() {
+            for (col, tile) in tiles.iter().enumerate() {
+                if let Some(service) = tile.as_ref().and_then(|tile| Service::of(&tile.content)) {
+                    places.push((RowCol::new(row, col), service));
+                }
+            }
+        }
+
+        // nothing new since the last time
+        let known = self.towns.iter().map(|town| town.places.len()).sum::<usize>();
+        if known == places.len() && places.iter().all(|(c, _)| self.town_at(*c).is_some()) {
+            return false;
+        }
+
+        // flood fill, each place joining the group of any place close enough
+        let mut groups: Vec<Vec<(RowCol, Service)>> = Vec::new();
+        let mut grouped = vec![false; places.len()];
+        for (i, place) in places.iter().enumerate() {
+            if grouped[i] {
+                continue;
+            }
+            grouped[i] = true;
+            let mut group = vec![*place];
+            let mut next = 0;
+            while next < group.len() {
+                let (c, _) = group[next];
+                for (other, done) in places.iter().zip(grouped.iter_mut()) {
+                    if !*done && c.row.abs_diff(other.0.row) <= self.radius && c.col.abs_diff(other.0.col) <= self.radius {
+                        *done = true;
+                        group.push(*other);
+                    }
+                }
+                next += 1;
+            }
+            groups.push(group);
+        }
+
+        // each group keeps the name of the known town it shares the most places with
+        let old = std::mem::take(&mut self.towns);
+        for places in groups {
+            let shared = |town: &Town| places.iter().filter(|(c, _)| town.places.iter().any(|(p, _)| p == c)).count();
+            let name = old
+                .iter()
+                .filter(|town| shared(town) > 0 && self.towns.iter().all(|t| t.name != town.name))
+                .max_by_key(|town| shared(town))
+                .map(|town| town.name.clone());
+            let is_new = name.is_none();
+            let name = name.unwrap_or_else(|| self.new_name());
+
+            let centroid = RowCol::new(
+                places.iter().map(|(c, _)| c.row).sum::<usize>() / places.len(),
+                places.iter().map(|(c, _)| c.col).sum::<usize>() / places.len(),
+            );
+            let town = Town { name, places, centroid };
+            if is_new {
+                println!("Found a new town: {town}");
+            }
+            self.towns.push(town);
+        }
+        true
+    }
+
+    // town a place belongs to
+    pub fn town_at(&self, c: RowCol) -> Option<&Town> {
+        self.towns.iter().find(|town| town.places.iter().any(|(p, _)| *p == c))
+    }
+
+    // town with the place closest to a location, offering the service if there is one
+    pub fn closest(&self, from: RowCol, service: Option<Service>) -> Option<&Town> {
+        self.towns
+            .iter()
+            .filter_map(|town| town.closest(from, service).map(|c| (town, from.distance(c))))
+            .min_by_key(|(_, distance)| *distance)
+            .map(|(town, _)| town)
+    }
+}
@@ -0,0 +1,243 @@
+use rand::rngs::StdRng;
+use rand::Rng;
+use std::collections::HashMap;
+
+use robotics_lib::world::environmental_conditions::{DayTime, WeatherType};
+use robotics_lib::world::tile::{Content, Tile};
+
+use crate::config::PioneerConfig;
+use crate::objective::Objective;
+use crate::objective::Objective::{Charging, Depositing, Exploring, Gathering, Selling, Sleeping, Waiting};
+use crate::planner::Goap;
+
+// what the robot knows about itself and the world when it wakes up,
+// handed to the strategy so that it can decide what to do for the day
+pub struct Snapshot<'a> {
+    pub backpack: &'a HashMap<Content, usize>,
+    pub backpack_size: usize,
+    pub energy: usize,
+    pub weather: WeatherType,
+    // weather predicted for the next 24 hours, if the forecast tool could tell
+    pub forecast: Option<WeatherType>,
+    pub known_map: &'a Vec<Vec<Option<Tile>>>,
+    // distance to the closest known location of each content, according to the tile mapper
+    // (only the contents that have been found at least once are present)
+    pub distances: HashMap<Content, usize>,
+    // average energy of a step in the current weather, as learned by the robot so far
+    pub step_energy: f32,
+    // coins the markets give for a unit of each content, as learned by the robot so far
+    pub prices: HashMap<Content, f32>,
+    pub config: &'a PioneerConfig,
+}
+
+// decides the objective for the day during the Praying phase
+// the robot takes care of finding the destination the objective needs, so a strategy only has to tell what to do:
+// - Sleeping to stay in the tent for the day
+// - Waiting(DayTime::Night) to reach a shelter and wait for the night there
+// - Gathering, Selling, Depositing and Exploring as usual
+// any random choice has to be made with the rng provided, so that runs can be replayed
+pub trait Strategy {
+    fn decide(&mut self, snapshot: &Snapshot, rng: &mut StdRng) -> Objective;
+
+    // objectives to carry out one after the other, for strategies that plan more than one step ahead
+    fn plan(&mut self, snapshot: &Snapshot, rng: &mut StdRng) -> Vec<Objective> {
+        vec![self.decide(snapshot, rng)]
+    }
+}
+
+// builds the strategy with the given name, as written in the config file
+pub fn by_name(name: &str) -> Option<Box<dyn Strategy>> {
+    match name {
+        | "heuristic" => Some(Box::new(Heuristic)),
+        | "utility" => Some(Box::new(Utility)),
+        | "goap" => Some(Box::new(Goap)),
+        | _ => None,
+    }
+}
+
+// the original decision making of the robot: bad weather first, then
+// sell when the backpack is almost full, gather when it's getting empty and explore otherwise
+pub struct Heuristic;
+
+impl Strategy for Heuristic {
+    fn decide(&mut self, snapshot: &Snapshot, rng: &mut StdRng) -> Objective {
+        // if current weather is bad, sleep for the day
+        if let WeatherType::TrentinoSnow | WeatherType::TropicalMonsoon = snapshot.weather {
+            println!("The weather today is {:?}", snapshot.weather);
+            return Sleeping;
+        }
+
+        // if the weather for the next day is bad, move close to a town
+        if let Some(weather @ (WeatherType::TrentinoSnow | WeatherType::TropicalMonsoon)) = snapshot.forecast.as_ref() {
+            println!("The weather tomorrow is {weather:?}");
+            print!("Decided to reach shelter from tomorrow's storm and ");
+            return Waiting(DayTime::Night);
+        }
+
+        let held = snapshot.backpack.values().sum::<usize>();
+
+        // if the backpack is almost full, go to the market and sell
+        if held >= snapshot.config.sell_threshold(snapshot.backpack_size) {
+            // select the item that would make the most money in the current held quantity
+            let sellable_content = content_to_sell(snapshot.backpack, |content| snapshot.price(content));
+
+            // check if it-s better to deposit coins in the bank before
+            if *snapshot.backpack.get(&Content::Coin(0)).unwrap_or(&0)
+                > *snapshot.backpack.get(&sellable_content).unwrap_or(&0)
+            {
+                println!("Decided to deposit my coins");
+                Depositing
+            } else {
+                println!("Decided to sell some {sellable_content}");
+                Selling(sellable_content)
+            }
+        }
+
+        // if the backpack is getting empty, gather some content
+        else if held <= snapshot.config.gather_threshold(snapshot.backpack_size) {
+            // select the item of which the robot holds most
+            // (always in the same order, since iterating over the backpack would give a different one each run)
+            let (mut max_content, mut max_quantity) = (Vec::new(), 0);
+            for content in SELLABLE {
+                let quantity = *snapshot.backpack.get(&content).unwrap_or(&0);
+                if quantity > max_quantity {
+                    max_quantity = quantity;
+                    max_content.clear();
+                    max_content.push(content);
+                } else if quantity == max_quantity {
+                    max_content.push(content);
+                }
+            }
+
+            // choose randomly if more than one have the same quantity
+            let range = max_content.len();
+            let target_content = max_content[rng.gen::<usize>() % range].clone();
+
+            println!("Decided to gather some {target_content}");
+            Gathering(target_content)
+        }
+
+        // if there is nothing else to do, explore
+        else {
+            println!("Decided to explore");
+            Exploring
+        }
+    }
+}
+
+// picks the objective that is expected to earn the most coins for each unit of energy spent,
+// instead of following fixed thresholds. Weather still comes first, as the robot can't do anything during a storm
+pub struct Utility;
+
+// rough estimates used to turn distances and quantities into energy
+// (steps are only estimated until the robot has learned how much they cost)
+pub(crate) const STEP_ENERGY: f32 = 3.;
+pub(crate) const DESTROY_ENERGY: f32 = 3.;
+pub(crate) const SPYGLASS_ENERGY: f32 = 100.;
+// maximum energy level of the robot
+pub(crate) const MAX_ENERGY: usize = 1000;
+
+impl Utility {
+    // scores every objective that can be carried out right now
+    fn score(&self, snapshot: &Snapshot) -> Vec<(Objective, f32)> {
+        let held = snapshot.backpack.values().sum::<usize>();
+        let quantity = |content: &Content| *snapshot.backpack.get(content).unwrap_or(&0);
+        let distance = |content: &Content| snapshot.distances.get(content).map(|d| *d as f32);
+        // objectives that cost more than the energy left are worth nothing today
+        let worth = |coins: f32, energy: f32| if energy > snapshot.energy as f32 { 0. } else { coins / energy };
+        let mut scores = Vec::new();
+
+        for content in SELLABLE {
+            // gathering goes on until the backpack is full enough to go selling
+            let amount = snapshot.config.sell_threshold(snapshot.backpack_size).saturating_sub(held);
+            if let (true, Some(d)) = (amount > 0, distance(&content)) {
+                let coins = amount as f32 * snapshot.price(&content);
+                scores.push((Gathering(content.clone()), worth(coins, d * snapshot.step_energy + amount as f32 * DESTROY_ENERGY)));
+            }
+
+            if let (true, Some(d)) = (quantity(&content) > 0, distance(&Content::Market(0))) {
+                let coins = quantity(&content) as f32 * snapshot.price(&content);
+                scores.push((Selling(content.clone()), worth(coins, d * snapshot.step_energy + 1.)));
+            }
+        }
+
+        // coins in the bank are worth as much as the ones in the backpack, but they also count towards the score
+        if let (true, Some(d)) = (quantity(&Content::Coin(0)) > 0, distance(&Content::Bank(0..0))) {
+            let coins = quantity(&Content::Coin(0)) as f32;
+            scores.push((Depositing, worth(coins, d * snapshot.step_energy + 1.)));
+        }
+
+        // exploring is worth as much as the fraction of the map still unknown,
+        // as it's the only way to find new content
+        let total = snapshot.known_map.len().pow(2).max(1) as f32;
+        let unknown = snapshot.known_map.iter().flatten().filter(|tile| tile.is_none()).count() as f32;
+        scores.push((Exploring, worth(unknown / total * snapshot.backpack_size as f32, SPYGLASS_ENERGY)));
+
+        // charging doesn't earn anything, but it becomes the best choice when the robot is running low
+        let missing = 1. - snapshot.energy as f32 / MAX_ENERGY as f32;
+        scores.push((Charging(snapshot.config.recharge_to), missing.powi(3)));
+
+        scores
+    }
+}
+
+impl Strategy for Utility {
+    fn decide(&mut self, snapshot: &Snapshot, _rng: &mut StdRng) -> Objective {
+        if let WeatherType::TrentinoSnow | WeatherType::TropicalMonsoon = snapshot.weather {
+            println!("The weather today is {:?}", snapshot.weather);
+            return Sleeping;
+        }
+        if let Some(weather @ (WeatherType::TrentinoSnow | WeatherType::TropicalMonsoon)) = snapshot.forecast.as_ref() {
+            println!("The weather tomorrow is {weather:?}");
+            print!("Decided to reach shelter from tomorrow's storm and ");
+            return Waiting(DayTime::Night);
+        }
+
+        let mut scores = self.score(snapshot);
+        scores.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        // log the reasoning behind the choice
+        println!("Scores (coins per energy):");
+        for (objective, score) in scores.iter() {
+            println!("  {score:>8.4} {objective}");
+        }
+
+        let (objective, score) = scores.swap_remove(0);
+        println!("Decided on {objective} ({score:.4})");
+        objective
+    }
+}
+
+// contents the robot gathers and sells
+pub(crate) const SELLABLE: [Content; 3] = [Content::Rock(0), Content::Tree(0), Content::Fish(0)];
+
+// coins the markets are expected to give for a unit of each content, until the robot has sold some
+pub(crate) fn default_price(content: &Content) -> f32 {
+    match content {
+        | Content::Rock(_) => 1.,
+        | Content::Tree(_) => 2.,
+        | Content::Fish(_) | Content::Coin(_) => 3.,
+        | _ => 0.,
+    }
+}
+
+impl Snapshot<'_> {
+    pub fn price(&self, content: &Content) -> f32 {
+        self.prices.get(&content.to_default()).copied().unwrap_or_else(|| default_price(content))
+    }
+}
+
+// returns the best content to sell at the moment, based on quantity
+// owned and price
+pub(crate) fn content_to_sell(backpack: &HashMap<Content, usize>, price: impl Fn(&Content) -> f32) -> Content {
+    let (mut max_content, mut max_cost) = (Content::None, 0.);
+
+    for content in SELLABLE {
+        let cost = *backpack.get(&content).unwrap_or(&0) as f32 * price(&content);
+        if cost > max_cost {
+            max_cost = cost;
+            max_content = content;
+        }
+    }
+    max_content
+}
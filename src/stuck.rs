@@ -0,0 +1,100 @@
+use std::collections::VecDeque;
+use std::fmt::{Display, Formatter};
+
+use crate::config::PioneerConfig;
+use crate::coords::RowCol;
+
+// ways in which the robot can get stuck while moving
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Stuck {
+    // going back and forth between two tiles
+    PingPong,
+    // going around the same few tiles, the number being the length of the cycle
+    Cycle(usize),
+    // not getting any closer to the destination
+    NoProgress,
+}
+
+impl Display for Stuck {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            | Stuck::PingPong => write!(f, "going back and forth"),
+            | Stuck::Cycle(length) => write!(f, "going around in a cycle of {length} tiles"),
+            | Stuck::NoProgress => write!(f, "not getting any closer"),
+        }
+    }
+}
+
+// keeps track of the positions of the robot while it's moving to a destination,
+// and tells when it looks like it's not going to get there
+pub struct StuckDetector {
+    destination: Option<RowCol>,
+    // positions and distances from the destination, one for each tick, the latest being the last one
+    history: VecDeque<(RowCol, usize)>,
+    capacity: usize,
+    max_cycle: usize,
+    progress_ticks: usize,
+}
+
+impl StuckDetector {
+    pub fn new(config: &PioneerConfig) -> Self {
+        StuckDetector {
+            destination: None,
+            history: VecDeque::new(),
+            capacity: config.stuck_history.max(config.stuck_progress_ticks + 1).max(config.stuck_max_cycle * 2),
+            max_cycle: config.stuck_max_cycle,
+            progress_ticks: config.stuck_progress_ticks,
+        }
+    }
+
+    // records the position of the robot, the history starts over when the destination changes
+    pub fn record(&mut self, position: RowCol, destination: Option<RowCol>) {
+        if destination != self.destination {
+            self.clear();
+            self.destination = destination;
+        }
+        let distance = destination.map(|destination| position.distance(destination)).unwrap_or(0);
+        if self.history.len() == self.capacity {
+            self.history.pop_front();
+        }
+        self.history.push_back((position, distance));
+    }
+
+    pub fn clear(&mut self) {
+        self.history.clear();
+    }
+
+    pub fn check(&self) -> Option<Stuck> {
+        let positions = self.history.iter().map(|(position, _)| *position).collect::<Vec<_>>();
+        let n = positions.len();
+
+        // A-B-A-B
+        if n >= 4 && positions[n - 1] == positions[n - 3] && positions[n - 2] == positions[n - 4] && positions[n - 1] != positions[n - 2] {
+            return Some(Stuck::PingPong);
+        }
+
+        // the last positions repeat the ones right before them, without standing still
+        for length in 3..=self.max_cycle {
+            if n >= length * 2
+                && (n - length..n).all(|i| positions[i] == positions[i - length])
+                && (n - length + 1..n).any(|i| positions[i] != positions[i - 1])
+            {
+                return Some(Stuck::Cycle(length));
+            }
+        }
+
+        // the closest the robot got in the last ticks is no closer than where it was before them
+        if self.destination.is_some() && n > self.progress_ticks {
+            let (_, before) = self.history[n - self.progress_ticks - 1];
+            if before == 0 {
+                return None;
+            }
+            let closest = self.history.iter().skip(n - self.progress_ticks).map(|(_, distance)| *distance).min();
+            if closest.map(|closest| closest >= before).unwrap_or(false) {
+                return Some(Stuck::NoProgress);
+            }
+        }
+
+        None
+    }
+}
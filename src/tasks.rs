@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+
+use robotics_lib::world::tile::Content;
+
+use crate::config::PioneerConfig;
+use crate::coords::RowCol;
+use crate::objective::Objective;
+use crate::objective::Objective::{Charging, Depositing, Exploring, Gathering, Moving, Selling};
+
+// what a task needs to know about the robot to check if it can still be carried out
+pub struct TaskState<'a> {
+    pub energy: usize,
+    pub backpack: &'a HashMap<Content, usize>,
+}
+
+// maximum amount of ticks and energy a task is allowed to take before it's considered stuck
+#[derive(Clone, Debug)]
+pub struct Budget {
+    pub ticks: usize,
+    pub energy: usize,
+}
+
+// an objective on the task stack, along with the destination it was heading to when it got interrupted
+// (the compass only holds one destination at a time, so it needs to be restored when the task is resumed)
+#[derive(Clone, Debug)]
+pub struct Task {
+    pub objective: Objective,
+    pub destination: Option<RowCol>,
+    budget: Option<Budget>,
+    spent: Budget,
+}
+
+impl Task {
+    pub fn new(objective: Objective) -> Self {
+        Task {
+            objective,
+            destination: None,
+            budget: None,
+            spent: Budget {
+                ticks: 0,
+                energy: 0,
+            },
+        }
+    }
+
+    // for tasks queued ahead of time, the compass is pointed there once the task is reached
+    pub fn with_destination(mut self, destination: RowCol) -> Self {
+        self.destination = Some(destination);
+        self
+    }
+
+    // sets the budget of the task, based on how far away its destination is
+    // tasks that only wait for something to happen (charging, sleeping...) have no budget
+    pub fn with_budget(mut self, distance: usize, config: &PioneerConfig) -> Self {
+        self.budget = match self.objective {
+            | Moving(_) => Some(Budget {
+                ticks: config.moving_budget_ticks + distance * config.moving_budget_ticks_per_tile,
+                energy: config.moving_budget_energy + distance * config.moving_budget_energy_per_tile,
+            }),
+            | Gathering(_) | Selling(_) | Depositing | Exploring => Some(Budget {
+                ticks: config.task_budget_ticks,
+                energy: config.task_budget_energy,
+            }),
+            | _ => None,
+        };
+        self
+    }
+
+    // whether the task took more than it was allowed to
+    pub fn over_budget(&self) -> bool {
+        self.budget
+            .as_ref()
+            .map(|budget| self.spent.ticks > budget.ticks || self.spent.energy > budget.energy)
+            .unwrap_or(false)
+    }
+
+    // whether the task makes sense in the current state
+    // eg. there is no point in going to the market without anything to sell
+    pub fn precondition(&self, state: &TaskState) -> bool {
+        match &self.objective {
+            | Selling(content) => *state.backpack.get(content).unwrap_or(&0) > 0,
+            | Depositing => *state.backpack.get(&Content::Coin(0)).unwrap_or(&0) > 0,
+            | _ => true,
+        }
+    }
+
+    // whether there is nothing left to do for the task
+    // most tasks know when they are done by themselves, so they report it by popping themselves off the stack
+    pub fn is_complete(&self, state: &TaskState) -> bool {
+        match &self.objective {
+            | Charging(target_level) => state.energy >= *target_level,
+            | _ => false,
+        }
+    }
+}
+
+// ordered list of the objectives of the robot, the last one being the one it's currently working on
+// - set_objective in the robot replaces the current task
+// - set_next queues a task right after the current one
+// - interrupts (low energy, nightfall...) are pushed on top, and the interrupted task is resumed once they are done
+#[derive(Default)]
+pub struct TaskStack {
+    tasks: Vec<Task>,
+}
+
+impl TaskStack {
+    // objective currently being worked on, Objective::None if there is nothing to do
+    pub fn current(&self) -> Objective {
+        self.tasks.last().map(|task| task.objective.clone()).unwrap_or(Objective::None)
+    }
+
+    // objective that comes right after the current one
+    pub fn next(&self) -> Objective {
+        self.tasks
+            .iter()
+            .rev()
+            .nth(1)
+            .map(|task| task.objective.clone())
+            .unwrap_or(Objective::None)
+    }
+
+    pub fn top(&self) -> Option<&Task> {
+        self.tasks.last()
+    }
+
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    // replaces the current task, or starts a new one if there is none
+    pub fn replace(&mut self, task: Task) {
+        self.tasks.pop();
+        self.tasks.push(task);
+    }
+
+    // queues a task to be done right after the current one
+    pub fn queue(&mut self, task: Task) {
+        let position = self.tasks.len().saturating_sub(1);
+        self.tasks.insert(position, task);
+    }
+
+    pub fn push(&mut self, task: Task) {
+        self.tasks.push(task);
+    }
+
+    pub fn pop(&mut self) -> Option<Task> {
+        self.tasks.pop()
+    }
+
+    // only the current task spends its budget, interrupted ones are put on hold
+    pub fn spend_tick(&mut self) {
+        if let Some(task) = self.tasks.last_mut() {
+            task.spent.ticks += 1;
+        }
+    }
+
+    pub fn spend_energy(&mut self, energy: usize) {
+        if let Some(task) = self.tasks.last_mut() {
+            task.spent.energy += energy;
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use std::collections::{BTreeSet, HashMap};
+
+use robotics_lib::world::tile::{Content, Tile};
+
+use crate::coords::RowCol;
+use crate::cost_model::CostModel;
+use crate::objective::Objective;
+use crate::objective::Objective::{Depositing, Selling};
+use crate::pathfinder;
+
+// plans trips with more than one stop (a market for each content to sell, then a bank),
+// so that the robot doesn't have to walk back across the map for each of them
+
+// known markets and banks considered for each stop, the closest ones to the robot with room for the load
+pub const CANDIDATES: usize = 4;
+
+// a place to go to, and what to do once there
+#[derive(Clone, Debug)]
+pub struct Stop {
+    pub location: RowCol,
+    pub objective: Objective,
+}
+
+pub struct TourPlanner<'a> {
+    map: &'a [Vec<Option<Tile>>],
+    teleports: &'a BTreeSet<RowCol>,
+    cost_model: &'a CostModel,
+    // estimated energy between two locations, None if there is no known way
+    cache: HashMap<(RowCol, RowCol), Option<usize>>,
+}
+
+impl<'a> TourPlanner<'a> {
+    pub fn new(map: &'a [Vec<Option<Tile>>], teleports: &'a BTreeSet<RowCol>, cost_model: &'a CostModel) -> Self {
+        TourPlanner {
+            map,
+            teleports,
+            cost_model,
+            cache: HashMap::new(),
+        }
+    }
+
+    fn cost(&mut self, from: RowCol, to: RowCol) -> Option<usize> {
+        if from == to {
+            return Some(0);
+        }
+        let (map, teleports, costs) = (self.map, self.teleports, self.cost_model);
+        *self
+            .cache
+            .entry((from, to))
+            .or_insert_with(|| pathfinder::route(map, teleports, costs, from, to).map(|route| route.cost))
+    }
+
+    // cheapest tour that sells each content at one of the markets and then deposits at one of the banks,
+    // along with its estimated energy. Without known banks the tour just ends after selling
+    pub fn plan(
+        &mut self,
+        start: RowCol,
+        sell: &[Content],
+        markets: &[RowCol],
+        banks: &[RowCol],
+    ) -> Option<(Vec<Stop>, usize)> {
+        if sell.is_empty() || markets.is_empty() {
+            return None;
+        }
+
+        let mut best: Option<(Vec<Stop>, usize)> = None;
+        for order in permutations(sell) {
+            let mut layers = order
+                .iter()
+                .map(|content| markets.iter().map(|m| Stop { location: *m, objective: Selling(content.clone()) }).collect())
+                .collect::<Vec<Vec<Stop>>>();
+            if !banks.is_empty() {
+                layers.push(banks.iter().map(|b| Stop { location: *b, objective: Depositing }).collect());
+            }
+
+            if let Some((stops, cost)) = self.cheapest_path(start, &layers) {
+                if best.as_ref().map(|(_, best_cost)| cost < *best_cost).unwrap_or(true) {
+                    best = Some((stops, cost));
+                }
+            }
+        }
+        best
+    }
+
+    // picks one stop from each layer, in order, minimizing the total cost
+    fn cheapest_path(&mut self, start: RowCol, layers: &[Vec<Stop>]) -> Option<(Vec<Stop>, usize)> {
+        // cheapest way found so far to end at each stop of the current layer
+        let mut paths: Vec<(Vec<Stop>, usize)> = vec![(Vec::new(), 0)];
+        for layer in layers {
+            let mut next_paths = Vec::new();
+            for stop in layer {
+                let mut cheapest: Option<(Vec<Stop>, usize)> = None;
+                for (path, cost) in paths.iter() {
+                    let from = path.last().map(|s| s.location).unwrap_or(start);
+                    if let Some(step) = self.cost(from, stop.location) {
+                        if cheapest.as_ref().map(|(_, c)| cost + step < *c).unwrap_or(true) {
+                            let mut path = path.clone();
+                            path.push(stop.clone());
+                            cheapest = Some((path, cost + step));
+                        }
+                    }
+                }
+                next_paths.extend(cheapest);
+            }
+            if next_paths.is_empty() {
+                return None;
+            }
+            paths = next_paths;
+        }
+        paths.into_iter().min_by_key(|(_, cost)| *cost)
+    }
+}
+
+fn permutations(items: &[Content]) -> Vec<Vec<Content>> {
+    if items.len() <= 1 {
+        return vec![items.to_vec()];
+    }
+    let mut result = Vec::new();
+    for i in 0..items.len() {
+        let mut rest = items.to_vec();
+        let first = rest.remove(i);
+        for mut permutation in permutations(&rest) {
+            permutation.insert(0, first.clone());
+            result.push(permutation);
+        }
+    }
+    result
+}
@@ -0,0 +1,77 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Write;
+
+use crate::objective::Objective;
+
+// why the objective of the robot changed
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Reason {
+    // chosen by the strategy or by the pilot in the Praying phase
+    Decided,
+    PilotChoice,
+    // nothing left to do, time to pray
+    Idle,
+    // interrupts
+    Nightfall,
+    LowEnergy,
+    TooRich,
+    // destinations
+    DestinationFound,
+    DestinationReached,
+    DestinationUnavailable,
+    Frontier,
+    SpyglassHit,
+    Shelter,
+    SleepSpotFound,
+    NotFound,
+    Unreachable,
+    OverBudget,
+    Stuck,
+    // tasks carried out
+    TaskDone,
+    WaitOver,
+    Charged,
+    TentPlaced,
+    BackpackFull,
+    TransactionDone,
+    Depleted,
+    NotEnoughRocks,
+}
+
+// one line of the trace, describing a change of objective along with the state of the robot
+#[derive(Serialize)]
+pub struct Record {
+    pub tick: usize,
+    pub day: usize,
+    pub time_of_day: String,
+    pub position: (usize, usize),
+    pub energy: usize,
+    pub backpack: BTreeMap<String, usize>,
+    pub old: Objective,
+    pub new: Objective,
+    pub next: Objective,
+    pub reason: Reason,
+}
+
+// writes every decision of the robot to a JSON Lines file, to analyze runs offline
+pub struct Trace {
+    file: File,
+}
+
+impl Trace {
+    pub fn create(path: &str) -> std::io::Result<Trace> {
+        Ok(Trace {
+            file: File::create(path)?,
+        })
+    }
+
+    // each record is written right away, so that the trace is complete even if the program is killed
+    pub fn record(&mut self, record: &Record) {
+        if let Ok(line) = serde_json::to_string(record) {
+            let _ = writeln!(self.file, "{line}");
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::mem::{self, Discriminant};
+
+use robotics_lib::world::tile::{Content, Tile};
+use spyglass::spyglass::SpyglassResult;
+
+use crate::config::PioneerConfig;
+use crate::coords::RowCol;
+
+// the spyglass sometimes returns tiles that don't match what it was looking for,
+// and sometimes reports content that isn't there once the robot gets to it:
+// its results go through here, so that only the hits the known map agrees with are handed out,
+// and each of those is checked again once the robot is there (or forgotten if it never gets there)
+
+// what happened to the tiles returned by a single use of the spyglass
+#[derive(Clone, Copy, Debug, Default)]
+pub struct QueryStats {
+    pub returned: usize,
+    // not matching what the spyglass was looking for
+    pub mismatched: usize,
+    // the known map said something else was there before the spyglass looked
+    pub contradicted: usize,
+    pub kept: usize,
+}
+
+impl Display for QueryStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} tiles returned, {} not matching, {} contradicted by the map, {} kept",
+            self.returned, self.mismatched, self.contradicted, self.kept
+        )
+    }
+}
+
+#[derive(Default)]
+pub struct VerifiedSpyglass {
+    // hits handed out and not checked yet, with the content they were supposed to hold and the day they were found
+    pending: HashMap<RowCol, (Discriminant<Content>, usize)>,
+    expiry_days: usize,
+    // hits checked once the robot got there, across all queries
+    confirmed: usize,
+    false_positives: usize,
+}
+
+impl VerifiedSpyglass {
+    pub fn new(config: &PioneerConfig) -> Self {
+        VerifiedSpyglass {
+            expiry_days: config.spyglass_hit_expiry_days,
+            ..VerifiedSpyglass::default()
+        }
+    }
+
+    // keeps the tiles of the result that match and that the known map agrees with, closest first
+    // the map has to be the one from before the spyglass was used, as the spyglass adds what it sees to it
+    pub fn verify(
+        &self,
+        result: SpyglassResult,
+        map: &[Vec<Option<Tile>>],
+        from: RowCol,
+        matches: impl Fn(&Tile) -> bool,
+    ) -> (Vec<(Tile, RowCol)>, QueryStats) {
+        let SpyglassResult::Stopped(tiles) = result else {
+            return (Vec::new(), QueryStats::default());
+        };
+
+        let mut stats = QueryStats {
+            returned: tiles.len(),
+            ..QueryStats::default()
+        };
+        let mut hits = Vec::new();
+        for (tile, row, col) in tiles {
+            let c = RowCol::new(row, col);
+            // tiles the robot didn't know about yet can't be contradicted
+            let known = map.get(row).and_then(|tiles| tiles.get(col)?.as_ref());
+            if !matches(&tile) {
+                stats.mismatched += 1;
+            } else if known.map(|known| !matches(known)).unwrap_or(false) {
+                stats.contradicted += 1;
+            } else {
+                hits.push((tile, c));
+            }
+        }
+        hits.sort_by_key(|(_, c)| from.distance(*c));
+        stats.kept = hits.len();
+        (hits, stats)
+    }
+
+    // the robot is heading to a hit, it will be checked once it gets there
+    pub fn follow(&mut self, hit: &(Tile, RowCol), day: usize) {
+        self.pending.insert(hit.1, (mem::discriminant(&hit.0.content), day));
+    }
+
+    // hits the robot didn't get to in time, most likely because it had something else to do
+    pub fn forget_expired(&mut self, day: usize) {
+        self.pending.retain(|_, (_, found)| *found + self.expiry_days > day);
+    }
+
+    pub fn is_pending(&self, c: RowCol) -> bool {
+        self.pending.contains_key(&c)
+    }
+
+    // checks a pending hit against the tile the robot sees now, returns false for a false positive
+    pub fn confirm(&mut self, c: RowCol, tile: Option<&Tile>) -> bool {
+        let Some((expected, _)) = self.pending.remove(&c) else {
+            return true;
+        };
+        let confirmed = tile.map(|tile| mem::discriminant(&tile.content) == expected).unwrap_or(false);
+        if confirmed {
+            self.confirmed += 1;
+        } else {
+            self.false_positives += 1;
+        }
+        confirmed
+    }
+}
+
+impl Display for VerifiedSpyglass {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} hits confirmed and {} false positives so far", self.confirmed, self.false_positives)
+    }
+}
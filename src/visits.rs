@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+use robotics_lib::world::tile::Content;
+
+use crate::config::PioneerConfig;
+use crate::coords::RowCol;
+
+// how the last visit to a place went
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    // the robot got there, nothing else happened yet
+    Reached,
+    Gathered,
+    Sold,
+    Deposited,
+    // a market or bank that couldn't take everything
+    Depleted,
+    // the content the robot came for wasn't there
+    NothingThere,
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            | Outcome::Reached => write!(f, "reached"),
+            | Outcome::Gathered => write!(f, "gathered"),
+            | Outcome::Sold => write!(f, "sold"),
+            | Outcome::Deposited => write!(f, "deposited"),
+            | Outcome::Depleted => write!(f, "depleted"),
+            | Outcome::NothingThere => write!(f, "nothing there"),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Visit {
+    pub first_day: usize,
+    pub last_day: usize,
+    // content of the tile the last time the robot was there
+    pub found: Content,
+    pub outcome: Outcome,
+}
+
+// places the robot has been to, so that exploring doesn't keep leading it to the same town
+pub struct VisitLog {
+    visits: HashMap<RowCol, Visit>,
+    // days after which a place counts as unexplored again
+    revisit_days: usize,
+}
+
+impl VisitLog {
+    pub fn new(config: &PioneerConfig) -> Self {
+        VisitLog {
+            visits: HashMap::new(),
+            revisit_days: config.revisit_days,
+        }
+    }
+
+    pub fn record(&mut self, c: RowCol, day: usize, found: Content, outcome: Outcome) {
+        let visit = self.visits.entry(c).or_insert(Visit {
+            first_day: day,
+            last_day: day,
+            found: found.clone(),
+            outcome,
+        });
+        if visit.outcome != outcome {
+            println!("Visit to {c}: {outcome}");
+        }
+        visit.last_day = day;
+        visit.found = found;
+        visit.outcome = outcome;
+    }
+
+    pub fn get(&self, c: RowCol) -> Option<&Visit> {
+        self.visits.get(&c)
+    }
+
+    // whether exploring should leave the place out for now
+    pub fn visited_recently(&self, c: RowCol, day: usize) -> bool {
+        self.visits.get(&c).map(|visit| visit.last_day + self.revisit_days > day).unwrap_or(false)
+    }
+}